@@ -0,0 +1,45 @@
+//! Generates the xz-compressed dictionary of the `bundled-ipadic-xz` feature from
+//! `ipadic/ipadic.zip` so only one archive has to be kept in the repository.
+
+#[cfg(feature = "bundled-ipadic-xz")]
+extern crate xz2;
+#[cfg(feature = "bundled-ipadic-xz")]
+extern crate zip;
+
+#[cfg(feature = "bundled-ipadic-xz")]
+fn main() {
+    use std::env;
+    use std::fs::File;
+    use std::io::{self, Cursor, Write};
+    use std::path::Path;
+
+    use xz2::write::XzEncoder;
+    use zip::write::FileOptions;
+    use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+    let input = "ipadic/ipadic.zip";
+    println!("cargo:rerun-if-changed={}", input);
+
+    // xz compresses much better without zip's own compression, so store the files uncompressed
+    let mut archive = ZipArchive::new(File::open(input).unwrap()).unwrap();
+    let mut stored = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).unwrap();
+        if file.is_dir() {
+            stored.add_directory(file.name(), options).unwrap();
+        } else {
+            stored.start_file(file.name(), options).unwrap();
+            io::copy(&mut file, &mut stored).unwrap();
+        }
+    }
+    let stored = stored.finish().unwrap().into_inner();
+
+    let output = Path::new(&env::var("OUT_DIR").unwrap()).join("ipadic.zip.xz");
+    let mut encoder = XzEncoder::new(File::create(output).unwrap(), 9);
+    encoder.write_all(&stored).unwrap();
+    encoder.finish().unwrap();
+}
+
+#[cfg(not(feature = "bundled-ipadic-xz"))]
+fn main() {}
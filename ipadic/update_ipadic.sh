@@ -1,8 +1,10 @@
 #!/bin/env bash
 
-# Source for igo.jar: https://osdn.net/projects/igo/
+# Compiles mecab-ipadic (the `mecab` submodule) into the igo format without needing Java. The
+# bundled dictionary is disabled as it's the one being regenerated.
+cargo run --release --no-default-features --features compiler --bin compile-dictionary -- mecab/mecab-ipadic ipadic EUC-JP
 
 rm ipadic.zip
-java -cp igo-0.4.5.jar net.reduls.igo.bin.BuildDic ipadic mecab/mecab-ipadic EUC-JP
+# The archive of the `bundled-ipadic-xz` feature is generated from this one by `build.rs`
 zip -r9 ipadic.zip ipadic
 rm -r ipadic
@@ -0,0 +1,23 @@
+//! Compile mecab dictionary sources into an igo dictionary, replacing igo's `BuildDic`.
+//!
+//! Usage: `compile-dictionary <input directory> <output directory> [encoding]`
+
+extern crate romanize;
+
+use std::env;
+use std::process;
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if args.len() < 2 || args.len() > 3 {
+        eprintln!("Usage: compile-dictionary <input directory> <output directory> [encoding]");
+        eprintln!("The encoding of the sources defaults to EUC-JP (used by mecab-ipadic).");
+        process::exit(2);
+    }
+    let encoding = args.get(2).map_or("EUC-JP", |encoding| &encoding[..]);
+
+    if let Err(e) = romanize::compile_dictionary(&args[0], &args[1], encoding) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
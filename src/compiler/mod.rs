@@ -0,0 +1,525 @@
+//! Compiler for mecab dictionary sources (e.g. mecab-ipadic) into the format of igo.
+//!
+//! This is a port of igo's `BuildDic` and produces the same files.
+
+mod trie;
+
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use encoding_rs::Encoding;
+
+use self::trie::Trie;
+use error::RomanizeError;
+
+/// Prefix of the keys of unknown word categories from `unk.def` in the trie.
+const UNKNOWN_PREFIX: &str = "\u{2}";
+
+/// Compile the mecab dictionary sources in `input_dir` (`*.csv`, `unk.def`, `matrix.def` and
+/// `char.def`) into an igo dictionary in `output_dir`, which is created if needed. `encoding` is
+/// the label of the encoding of the sources, e.g. `EUC-JP` for mecab-ipadic or `UTF-8` for UniDic
+/// (see [`DictionaryFormat`](::DictionaryFormat)). Requires the `compiler` feature.
+///
+/// # Examples
+///
+/// ```no_run
+/// use romanize::{compile_dictionary, Romanizer};
+///
+/// compile_dictionary("mecab-ipadic", "ipadic", "EUC-JP").unwrap();
+/// let romanizer = Romanizer::from_dictionary_dir("ipadic").unwrap();
+/// ```
+pub fn compile_dictionary<P: AsRef<Path>, Q: AsRef<Path>>(
+    input_dir: P,
+    output_dir: Q,
+    encoding: &str,
+) -> Result<(), RomanizeError> {
+    let (input_dir, output_dir) = (input_dir.as_ref(), output_dir.as_ref());
+    let encoding = Encoding::for_label(encoding.as_bytes()).ok_or_else(|| {
+        RomanizeError::Dictionary(format!("unknown source encoding \"{}\"", encoding))
+    })?;
+    fs::create_dir_all(output_dir)?;
+
+    let mut sources = vec![(input_dir.join("unk.def"), UNKNOWN_PREFIX)];
+    let mut csv_files = Vec::new();
+    for entry in fs::read_dir(input_dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension() == Some(OsStr::new("csv")) {
+            csv_files.push(path);
+        }
+    }
+    // Sorted so entries with the same context ids and cost are picked deterministically
+    csv_files.sort();
+    sources.extend(csv_files.into_iter().map(|path| (path, "")));
+
+    let mut lexicon = Vec::new();
+    for (path, prefix) in &sources {
+        lexicon.push((path, prefix, read_source(path, encoding)?));
+    }
+
+    let mut keys = Vec::new();
+    for (path, prefix, text) in &lexicon {
+        for (i, line) in text.lines().enumerate() {
+            let entry = Entry::parse(line).map_err(|reason| parse_error(path, i, reason))?;
+            keys.push(utf16(prefix, entry.surface));
+        }
+    }
+    let trie = Trie::build(keys);
+    fs::write(output_dir.join("word2id"), trie.to_bytes())?;
+
+    let mut words = vec![Vec::new(); trie.len()];
+    for (path, prefix, text) in &lexicon {
+        for (i, line) in text.lines().enumerate() {
+            let entry = Entry::parse(line).map_err(|reason| parse_error(path, i, reason))?;
+            let id = trie.id(&utf16(prefix, entry.surface)).unwrap();
+            words[id].push(entry);
+        }
+    }
+    write_words(output_dir, words)?;
+
+    let matrix_def = input_dir.join("matrix.def");
+    // The matrix only contains numbers and is always read as UTF-8
+    let matrix = compile_matrix(&read_source(&matrix_def, encoding_rs::UTF_8)?)
+        .map_err(|(i, reason)| parse_error(&matrix_def, i, reason))?;
+    fs::write(output_dir.join("matrix.bin"), matrix)?;
+
+    let char_def = input_dir.join("char.def");
+    let (categories, code2category) =
+        compile_char_categories(&read_source(&char_def, encoding)?, &trie)
+            .map_err(|(i, reason)| parse_error(&char_def, i, reason))?;
+    fs::write(output_dir.join("char.category"), categories)?;
+    fs::write(output_dir.join("code2category"), code2category)?;
+
+    Ok(())
+}
+
+/// A line of a lexicon CSV or `unk.def`.
+#[derive(Clone)]
+struct Entry<'a> {
+    surface: &'a str,
+    left_id: i16,
+    right_id: i16,
+    cost: i16,
+    /// The remaining fields (part-of-speech, reading, ...)
+    features: &'a str,
+}
+
+impl<'a> Entry<'a> {
+    fn parse(line: &'a str) -> Result<Entry<'a>, String> {
+        // The surface is never empty, so this also supports "," as surface
+        let first = line.chars().next().map_or(0, char::len_utf8);
+        let surface_end = line[first..]
+            .find(',')
+            .map(|i| first + i)
+            .ok_or("word surface must be terminated with ','")?;
+        let mut fields = line[surface_end + 1..].splitn(4, ',');
+        let mut number = |name: &str| -> Result<i16, String> {
+            let field = fields.next().unwrap_or("");
+            field
+                .parse()
+                .map_err(|_| format!("invalid {} \"{}\"", name, field))
+        };
+        let (left_id, right_id, cost) = (
+            number("left context id")?,
+            number("right context id")?,
+            number("cost")?,
+        );
+        let features = fields
+            .next()
+            .ok_or("word cost must be terminated with ','")?;
+
+        Ok(Entry {
+            surface: &line[..surface_end],
+            left_id,
+            right_id,
+            cost,
+            features,
+        })
+    }
+}
+
+/// Write `word.inf`, `word.dat` and `word.ary.idx` from the entries of each word id.
+fn write_words(output_dir: &Path, mut words: Vec<Vec<Entry>>) -> Result<(), RomanizeError> {
+    // Only keep the cheapest entry for each pair of context ids
+    for entries in &mut words {
+        entries.sort_by_key(|entry| (entry.left_id, entry.right_id, entry.cost));
+        entries.dedup_by_key(|entry| (entry.left_id, entry.right_id));
+    }
+    let entries = words.iter().flat_map(|entries| entries.iter());
+    let count = entries.clone().count();
+
+    let mut data = Vec::new();
+    let mut info = Vec::with_capacity((count + 1) * 10);
+    for entry in entries.clone() {
+        put_i32(&mut info, data.len() as i32);
+        data.extend(entry.features.encode_utf16());
+    }
+    put_i32(&mut info, data.len() as i32);
+    for entry in entries.clone() {
+        put_i16(&mut info, entry.left_id);
+    }
+    put_i16(&mut info, 0);
+    for entry in entries.clone() {
+        put_i16(&mut info, entry.right_id);
+    }
+    put_i16(&mut info, 0);
+    for entry in entries {
+        put_i16(&mut info, entry.cost);
+    }
+    put_i16(&mut info, 0);
+    fs::write(output_dir.join("word.inf"), info)?;
+
+    let mut dat = Vec::with_capacity(data.len() * 2);
+    for unit in data {
+        dat.extend_from_slice(&unit.to_le_bytes());
+    }
+    fs::write(output_dir.join("word.dat"), dat)?;
+
+    let mut idx = Vec::with_capacity((words.len() + 1) * 4);
+    let mut offset = 0;
+    for entries in &words {
+        put_i32(&mut idx, offset);
+        offset += entries.len() as i32;
+    }
+    put_i32(&mut idx, offset);
+    fs::write(output_dir.join("word.ary.idx"), idx)?;
+
+    Ok(())
+}
+
+/// Compile `matrix.def` into the contents of `matrix.bin`.
+fn compile_matrix(matrix_def: &str) -> Result<Vec<u8>, (usize, String)> {
+    let mut lines = matrix_def.lines().enumerate();
+    let mut next_line = || {
+        lines
+            .next()
+            .ok_or((0, "unexpected end of file".to_string()))
+    };
+
+    let (_, header) = next_line()?;
+    let mut dimensions = header.splitn(2, ' ').map(str::parse::<usize>);
+    let (left_size, right_size) = match (dimensions.next(), dimensions.next()) {
+        (Some(Ok(left)), Some(Ok(right))) => (left, right),
+        _ => return Err((0, format!("invalid matrix size \"{}\"", header))),
+    };
+
+    let mut costs = vec![0i16; left_size * right_size];
+    for left in 0..left_size {
+        for right in 0..right_size {
+            let (i, line) = next_line()?;
+            let fields = line.splitn(3, ' ').collect::<Vec<_>>();
+            let (expected_left, expected_right, cost) = match fields[..] {
+                [l, r, cost] => match (l.parse(), r.parse(), cost.parse()) {
+                    (Ok(l), Ok(r), Ok(cost)) => (l, r, cost),
+                    _ => return Err((i, format!("invalid connection cost \"{}\"", line))),
+                },
+                _ => return Err((i, format!("invalid connection cost \"{}\"", line))),
+            };
+            if (expected_left, expected_right) != (left, right) {
+                return Err((i, format!("expected context ids {} {}", left, right)));
+            }
+            costs[right * left_size + left] = cost;
+        }
+    }
+
+    let mut matrix = Vec::with_capacity(8 + costs.len() * 2);
+    put_i32(&mut matrix, left_size as i32);
+    put_i32(&mut matrix, right_size as i32);
+    for cost in costs {
+        put_i16(&mut matrix, cost);
+    }
+    Ok(matrix)
+}
+
+/// A character category from `char.def`.
+#[derive(Clone, Copy)]
+struct Category {
+    id: i32,
+    length: i32,
+    invoke: bool,
+    group: bool,
+}
+
+/// Compile `char.def` into the contents of `char.category` and `code2category`. The ids of the
+/// categories are the word ids of the corresponding entries of `unk.def`.
+fn compile_char_categories(
+    char_def: &str,
+    trie: &Trie,
+) -> Result<(Vec<u8>, Vec<u8>), (usize, String)> {
+    let mut categories = HashMap::new();
+    let mut mappings = Vec::new();
+    for (i, line) in char_def.lines().enumerate() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('0') {
+            mappings.push((i, line));
+            continue;
+        }
+
+        let fields = line.split_ascii_whitespace().collect::<Vec<_>>();
+        if fields.len() < 4 {
+            return Err((i, "too few fields in category definition".to_string()));
+        }
+        let flag = |field: &str, name: &str| match field {
+            "0" => Ok(false),
+            "1" => Ok(true),
+            _ => Err((i, format!("{} must be 0 or 1", name))),
+        };
+        let length = fields[3]
+            .parse()
+            .ok()
+            .filter(|&length| length >= 0)
+            .ok_or_else(|| (i, "LENGTH must be a non-negative integer".to_string()))?;
+        let id = trie
+            .id(&utf16(UNKNOWN_PREFIX, fields[0]))
+            .ok_or_else(|| (i, format!("category {} is missing in unk.def", fields[0])))?;
+        let category = Category {
+            id: id as i32,
+            length,
+            invoke: flag(fields[1], "INVOKE")?,
+            group: flag(fields[2], "GROUP")?,
+        };
+        categories.insert(fields[0], category);
+    }
+
+    let last_line = char_def.lines().count();
+    let category_id = |name: &str| -> Result<i32, (usize, String)> {
+        categories
+            .get(name)
+            .map(|category| category.id)
+            .ok_or_else(|| (last_line, format!("missing mandatory category {}", name)))
+    };
+    let (default_id, space_id) = (category_id("DEFAULT")?, category_id("SPACE")?);
+
+    // Category and compatibility mask of each UTF-16 code unit
+    let mut code2category = vec![(default_id, mask(default_id)); 0x10000];
+    for (i, line) in mappings {
+        let line = line.split('#').next().unwrap();
+        let fields = line.split_ascii_whitespace().collect::<Vec<_>>();
+        if fields.len() < 2 {
+            return Err((i, "invalid code to category mapping".to_string()));
+        }
+        let code = |code: &str| {
+            u16::from_str_radix(code.get(2..).unwrap_or(""), 16)
+                .map_err(|_| (i, format!("invalid UCS-2 code \"{}\"", code)))
+        };
+        let (start, end) = match fields[0].find("..") {
+            Some(pos) => (code(&fields[0][..pos])?, code(&fields[0][pos + 2..])?),
+            None => (code(fields[0])?, code(fields[0])?),
+        };
+        if start > end {
+            return Err((i, format!("invalid code range {}", fields[0])));
+        }
+
+        let mut ids = fields[1..].iter().map(|name| {
+            categories
+                .get(name)
+                .map(|category| category.id)
+                .ok_or_else(|| (i, format!("category {} is undefined", name)))
+        });
+        let id = ids.next().unwrap()?;
+        let mut compatible = mask(id);
+        for other in ids {
+            compatible |= mask(other?);
+        }
+        for entry in &mut code2category[start as usize..=end as usize] {
+            *entry = (id, compatible);
+        }
+    }
+    if code2category[0x20].0 != space_id {
+        return Err((
+            last_line,
+            "0x0020 is reserved for the SPACE category".to_string(),
+        ));
+    }
+
+    let mut sorted = categories.values().cloned().collect::<Vec<_>>();
+    sorted.sort_by_key(|category| category.id);
+    let mut char_category = Vec::with_capacity(sorted.len() * 16);
+    for category in sorted {
+        put_i32(&mut char_category, category.id);
+        put_i32(&mut char_category, category.length);
+        put_i32(&mut char_category, category.invoke as i32);
+        put_i32(&mut char_category, category.group as i32);
+    }
+
+    let mut code2category_bytes = Vec::with_capacity(0x10000 * 8);
+    for &(id, _) in &code2category {
+        put_i32(&mut code2category_bytes, id);
+    }
+    for &(_, mask) in &code2category {
+        put_i32(&mut code2category_bytes, mask);
+    }
+
+    Ok((char_category, code2category_bytes))
+}
+
+/// Bit of the category `id` in a compatibility mask. Like in igo, only the lowest 5 bits of the
+/// id are used.
+fn mask(id: i32) -> i32 {
+    1i32.wrapping_shl(id as u32)
+}
+
+fn read_source(path: &Path, encoding: &'static Encoding) -> Result<String, RomanizeError> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(_) if !path.exists() => return Err(RomanizeError::MissingDictionaryFile(path.into())),
+        Err(e) => return Err(e.into()),
+    };
+    let (text, _, malformed) = encoding.decode(&bytes);
+    if malformed {
+        return Err(RomanizeError::MalformedDictionaryFile(
+            path.into(),
+            format!("not valid {}", encoding.name()),
+        ));
+    }
+    Ok(text.into_owned())
+}
+
+fn parse_error(path: &Path, line: usize, reason: String) -> RomanizeError {
+    RomanizeError::MalformedDictionaryFile(
+        PathBuf::from(path),
+        format!("line {}: {}", line + 1, reason),
+    )
+}
+
+fn utf16(prefix: &str, surface: &str) -> Vec<u16> {
+    prefix
+        .encode_utf16()
+        .chain(surface.encode_utf16())
+        .collect()
+}
+
+fn put_i32(out: &mut Vec<u8>, value: i32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_i16(out: &mut Vec<u8>, value: i16) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dictionary::check_dictionary_dir;
+    use std::io::{Cursor, Read};
+    use tempfile::TempDir;
+    use zip::ZipArchive;
+
+    const CHAR_DEF: &str = "\
+DEFAULT 0 1 0 # Mandatory
+SPACE 0 1 0
+KANJI 0 0 2
+0x0020 SPACE
+0x4E00..0x9FA5 KANJI
+0x3007 KANJI DEFAULT
+";
+
+    fn write_sources(dir: &Path, char_def: &str) {
+        let files = [
+            ("char.def", char_def),
+            ("unk.def", "DEFAULT,0,0,500,名詞,一般,*,*,*,*,*\nSPACE,1,1,100,記号,空白,*,*,*,*,*\nKANJI,1,0,800,名詞,一般,*,*,*,*,*\n"),
+            ("matrix.def", "2 2\n0 0 10\n0 1 -20\n1 0 30\n1 1 40\n"),
+            ("nouns.csv", "東京,1,1,3000,名詞,固有名詞,地域,一般,*,*,東京,トウキョウ,トーキョー\n東,1,1,4000,名詞,一般,*,*,*,*,東,ヒガシ,ヒガシ\n東,1,1,5000,名詞,一般,*,*,*,*,東,アズマ,アズマ\n"),
+        ];
+        for &(name, contents) in &files {
+            let (bytes, _, _) = encoding_rs::EUC_JP.encode(contents);
+            fs::write(dir.join(name), bytes).unwrap();
+        }
+    }
+
+    #[test]
+    fn compile() {
+        let (input, output) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        write_sources(input.path(), CHAR_DEF);
+        compile_dictionary(input.path(), output.path(), "EUC-JP").unwrap();
+        check_dictionary_dir(output.path()).unwrap();
+
+        // 東 has only one entry left as both share the context ids
+        let idx = fs::read(output.path().join("word.ary.idx")).unwrap();
+        assert_eq!(idx.len(), 6 * 4);
+        assert_eq!(&idx[20..], &5i32.to_le_bytes());
+        let matrix = fs::read(output.path().join("matrix.bin")).unwrap();
+        assert_eq!(&matrix[8..10], &10i16.to_le_bytes());
+        assert_eq!(&matrix[10..12], &30i16.to_le_bytes());
+    }
+
+    #[test]
+    fn same_as_build_dic() {
+        let sources = [
+            (
+                "char.def",
+                include_str!("../../tests/fixtures/mini-ipadic/source/char.def"),
+            ),
+            (
+                "unk.def",
+                include_str!("../../tests/fixtures/mini-ipadic/source/unk.def"),
+            ),
+            (
+                "matrix.def",
+                include_str!("../../tests/fixtures/mini-ipadic/source/matrix.def"),
+            ),
+            (
+                "Noun.csv",
+                include_str!("../../tests/fixtures/mini-ipadic/source/Noun.csv"),
+            ),
+            (
+                "Others.csv",
+                include_str!("../../tests/fixtures/mini-ipadic/source/Others.csv"),
+            ),
+        ];
+        // Compiled with igo 0.4.5 (https://osdn.net/projects/igo/, no longer part of this
+        // repository) in `tests/fixtures/mini-ipadic`:
+        // java -cp igo-0.4.5.jar net.reduls.igo.bin.BuildDic mini-ipadic source UTF-8
+        // zip -r9 igo.zip mini-ipadic && rm -r mini-ipadic
+        let build_dic = include_bytes!("../../tests/fixtures/mini-ipadic/igo.zip");
+
+        let (input, output) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        for &(name, contents) in &sources {
+            fs::write(input.path().join(name), contents).unwrap();
+        }
+        compile_dictionary(input.path(), output.path(), "UTF-8").unwrap();
+
+        let mut archive = ZipArchive::new(Cursor::new(&build_dic[..])).unwrap();
+        let mut compared = 0;
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i).unwrap();
+            let name = match Path::new(file.name()).file_name() {
+                Some(name) if !file.name().ends_with('/') => name.to_owned(),
+                _ => continue,
+            };
+            let mut expected = Vec::new();
+            file.read_to_end(&mut expected).unwrap();
+            let actual = fs::read(output.path().join(&name)).unwrap();
+            assert!(actual == expected, "{:?} differs", name);
+            compared += 1;
+        }
+        assert_eq!(compared, 7);
+    }
+
+    #[test]
+    fn errors() {
+        let (input, output) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        write_sources(
+            input.path(),
+            &CHAR_DEF.replace("KANJI DEFAULT", "KANJI NUMERIC"),
+        );
+        match compile_dictionary(input.path(), output.path(), "EUC-JP") {
+            Err(RomanizeError::MalformedDictionaryFile(ref path, ref reason)) => {
+                assert_eq!(path, &input.path().join("char.def"));
+                assert_eq!(reason, "line 6: category NUMERIC is undefined");
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+
+        fs::remove_file(input.path().join("matrix.def")).unwrap();
+        match compile_dictionary(input.path(), output.path(), "EUC-JP") {
+            Err(RomanizeError::MissingDictionaryFile(ref path)) => {
+                assert_eq!(path, &input.path().join("matrix.def"));
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,322 @@
+//! Double-array trie mapping the surfaces of the dictionary to word ids (`word2id`).
+//!
+//! This mirrors the builder of igo (`net.reduls.igo.trie`) so the output is byte-for-byte
+//! identical to the one of `BuildDic`.
+
+use std::cmp::{self, Ordering};
+
+/// Initial value of unused base entries.
+const BASE_INIT: i32 = i32::MIN;
+/// Check code of nodes terminating a key.
+const TERMINATE_CODE: u16 = 0;
+/// Check code of unused nodes.
+const VACANT_CODE: u16 = 1;
+/// Largest UTF-16 code unit; the first `CODE_LIMIT` nodes are never allocated.
+const CODE_LIMIT: usize = 0xffff;
+
+/// A trie of UTF-16 keys. The id of a key is its index in the sorted set of all keys.
+pub struct Trie {
+    keys: Vec<Vec<u16>>,
+    base: Vec<i32>,
+    chck: Vec<u16>,
+    begs: Vec<i32>,
+    lens: Vec<i16>,
+    tail: Vec<u16>,
+}
+
+impl Trie {
+    pub fn build(mut keys: Vec<Vec<u16>>) -> Trie {
+        keys.sort();
+        keys.dedup();
+
+        let mut trie = Trie {
+            keys: Vec::new(),
+            base: Vec::new(),
+            chck: Vec::new(),
+            begs: Vec::new(),
+            lens: Vec::new(),
+            tail: Vec::new(),
+        };
+        let mut streams = keys
+            .iter()
+            .map(|key| KeyStream { key, cur: 0 })
+            .collect::<Vec<_>>();
+        let len = streams.len();
+        if len > 0 {
+            trie.build_impl(&mut Allocator::new(), &mut streams, 0, len, 0);
+        }
+        trie.keys = keys;
+        trie
+    }
+
+    /// Number of distinct keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// The id of `key` if it's part of the trie.
+    pub fn id(&self, key: &[u16]) -> Option<usize> {
+        self.keys.binary_search_by(|k| k[..].cmp(key)).ok()
+    }
+
+    fn build_impl(
+        &mut self,
+        alloc: &mut Allocator,
+        streams: &mut [KeyStream],
+        beg: usize,
+        end: usize,
+        root: usize,
+    ) {
+        if end - beg == 1 {
+            let rest = streams[beg].rest();
+            self.insert_tail(rest, root);
+            return;
+        }
+
+        let mut ends = Vec::new();
+        let mut codes = Vec::new();
+        let mut prev = VACANT_CODE;
+        for (i, stream) in streams.iter_mut().enumerate().take(end).skip(beg) {
+            let code = stream.read();
+            if code != prev {
+                prev = code;
+                codes.push(code);
+                ends.push(i);
+            }
+        }
+        ends.push(end);
+
+        let x = alloc.x_check(&codes);
+        for (i, &code) in codes.iter().enumerate() {
+            let node = self.set_node(code, root, x);
+            self.build_impl(alloc, streams, ends[i], ends[i + 1], node);
+        }
+    }
+
+    fn set_node(&mut self, code: u16, prev: usize, x: usize) -> usize {
+        let next = x + code as usize;
+        set(&mut self.base, prev, x as i32, BASE_INIT);
+        set(&mut self.chck, next, code, VACANT_CODE);
+        next
+    }
+
+    fn insert_tail(&mut self, rest: &[u16], node: usize) {
+        let id = self.begs.len() as i32;
+        set(&mut self.base, node, -id - 1, BASE_INIT);
+        self.begs.push(self.tail.len() as i32);
+        self.tail.extend_from_slice(rest);
+        self.lens.push(rest.len() as i16);
+    }
+
+    /// Serialize the trie in the format of igo's `word2id`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let (begs, lens, tail) = shrink_tail(&self.begs, &self.lens, &self.tail);
+
+        let used = self
+            .chck
+            .iter()
+            .rposition(|&code| code != VACANT_CODE)
+            .map_or(0, |i| i + 1);
+        let node_size = used + CODE_LIMIT;
+
+        let mut out = Vec::with_capacity(12 + node_size * 6 + begs.len() * 6 + tail.len() * 2);
+        put_i32(&mut out, node_size as i32);
+        put_i32(&mut out, begs.len() as i32);
+        put_i32(&mut out, tail.len() as i32);
+        for &beg in &begs {
+            put_i32(&mut out, beg);
+        }
+        for i in 0..node_size {
+            put_i32(&mut out, *self.base.get(i).unwrap_or(&BASE_INIT));
+        }
+        for &len in &lens {
+            put_i16(&mut out, len);
+        }
+        for i in 0..node_size {
+            put_u16(&mut out, *self.chck.get(i).unwrap_or(&VACANT_CODE));
+        }
+        for &unit in &tail {
+            put_u16(&mut out, unit);
+        }
+        out
+    }
+}
+
+struct KeyStream<'a> {
+    key: &'a [u16],
+    cur: usize,
+}
+
+impl<'a> KeyStream<'a> {
+    fn read(&mut self) -> u16 {
+        match self.key.get(self.cur) {
+            Some(&code) => {
+                self.cur += 1;
+                code
+            }
+            None => TERMINATE_CODE,
+        }
+    }
+
+    fn rest(&self) -> &'a [u16] {
+        &self.key[self.cur..]
+    }
+}
+
+/// Finds free base values with a doubly linked list of the unused nodes.
+struct Allocator {
+    // (prev, next) of every node; `next == 0` marks a used node
+    links: Vec<(usize, usize)>,
+    used_bases: Vec<bool>,
+}
+
+impl Allocator {
+    fn new() -> Allocator {
+        let mut alloc = Allocator {
+            links: vec![(0, 0)],
+            used_bases: Vec::new(),
+        };
+        alloc.resize(655_350);
+        alloc
+    }
+
+    /// Find a base `x` so `x + code` is free for all `codes` and reserve those nodes.
+    fn x_check(&mut self, codes: &[u16]) -> usize {
+        let mut cur = self.links[CODE_LIMIT].1;
+        loop {
+            // The free nodes after `CODE_LIMIT` always fit, but never compute a negative base
+            let x = match cur.checked_sub(codes[0] as usize) {
+                Some(x) => x,
+                None => {
+                    cur = self.next_free(cur);
+                    continue;
+                }
+            };
+            if !self.used_bases.get(x).cloned().unwrap_or(false) && self.can_allocate(codes, x) {
+                if x >= self.used_bases.len() {
+                    self.used_bases.resize(x + 1, false);
+                }
+                self.used_bases[x] = true;
+                for &code in codes {
+                    self.alloc(x + code as usize);
+                }
+                return x;
+            }
+            cur = self.next_free(cur);
+        }
+    }
+
+    /// The free node after `cur`, growing the list if `cur` is the last one.
+    fn next_free(&mut self, cur: usize) -> usize {
+        if self.links[cur].1 == 0 {
+            self.resize(0);
+        }
+        self.links[cur].1
+    }
+
+    fn can_allocate(&self, codes: &[u16], x: usize) -> bool {
+        codes[1..].iter().all(|&code| {
+            let node = x + code as usize;
+            node >= self.links.len() || self.links[node].1 != 0
+        })
+    }
+
+    fn alloc(&mut self, node: usize) {
+        while node >= self.links.len() - 1 {
+            self.resize(0);
+        }
+        let (prev, next) = self.links[node];
+        self.links[prev].1 = next;
+        self.links[next].0 = prev;
+        self.links[node].1 = 0;
+    }
+
+    fn resize(&mut self, hint: usize) {
+        let len = self.links.len();
+        let new_len = cmp::max(hint, len * 2);
+        self.links[len - 1].1 = len;
+        for i in len..new_len {
+            self.links.push((i - 1, i + 1));
+        }
+        self.links[new_len - 1].1 = 0;
+    }
+}
+
+/// Set `vec[index]`, growing `vec` with `fill` if needed.
+fn set<T: Copy>(vec: &mut Vec<T>, index: usize, value: T, fill: T) {
+    if index >= vec.len() {
+        vec.resize(index * 2 + 1, fill);
+    }
+    vec[index] = value;
+}
+
+/// Share the tails of keys which are suffixes of other tails.
+fn shrink_tail(begs: &[i32], lens: &[i16], tail: &[u16]) -> (Vec<i32>, Vec<i16>, Vec<u16>) {
+    let mut tails = begs
+        .iter()
+        .zip(lens)
+        .enumerate()
+        .map(|(id, (&beg, &len))| (id, &tail[beg as usize..beg as usize + len as usize]))
+        .collect::<Vec<_>>();
+    // Sort by the reversed strings in descending order so a suffix directly follows the longest
+    // string ending with it
+    tails.sort_by(|a, b| compare_reversed(b.1, a.1));
+
+    let mut new_begs = begs.to_vec();
+    let mut new_lens = lens.to_vec();
+    let mut new_tail = Vec::new();
+    for (i, &(id, s)) in tails.iter().enumerate() {
+        let mut beg = new_tail.len();
+        if i > 0 && tails[i - 1].1.ends_with(s) {
+            beg -= s.len();
+        } else {
+            new_tail.extend_from_slice(s);
+        }
+        new_begs[id] = beg as i32;
+        new_lens[id] = s.len() as i16;
+    }
+    (new_begs, new_lens, new_tail)
+}
+
+fn compare_reversed(a: &[u16], b: &[u16]) -> Ordering {
+    a.iter().rev().cmp(b.iter().rev())
+}
+
+fn put_i32(out: &mut Vec<u8>, value: i32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_i16(out: &mut Vec<u8>, value: i16) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(s: &str) -> Vec<u16> {
+        s.encode_utf16().collect()
+    }
+
+    #[test]
+    fn ids() {
+        let trie = Trie::build(vec![utf16("東京"), utf16("東"), utf16("京都"), utf16("東")]);
+        assert_eq!(trie.len(), 3);
+        assert_eq!(trie.id(&utf16("京都")), Some(0));
+        assert_eq!(trie.id(&utf16("東")), Some(1));
+        assert_eq!(trie.id(&utf16("東京")), Some(2));
+        assert_eq!(trie.id(&utf16("京")), None);
+    }
+
+    #[test]
+    fn shared_tails() {
+        let (begs, lens, tail) = shrink_tail(&[0, 3, 5], &[3, 2, 1], &utf16("abcbcd"));
+        assert_eq!(tail, utf16("dabc"));
+        assert_eq!(begs, vec![1, 2, 0]);
+        assert_eq!(lens, vec![3, 2, 1]);
+    }
+}
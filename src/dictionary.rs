@@ -0,0 +1,361 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crc32fast;
+use dirs;
+use tempfile::TempDir;
+#[cfg(feature = "xz2")]
+use xz2::read::XzDecoder;
+use zip::read::ZipFile;
+use zip::ZipArchive;
+
+use error::RomanizeError;
+
+/// Environment variable overriding the default cache directory.
+pub const CACHE_DIR_ENV: &str = "ROMANIZE_CACHE_DIR";
+
+/// The cache directory used if none is set explicitly: `$ROMANIZE_CACHE_DIR` or the `romanize`
+/// directory in the user's cache directory.
+pub fn default_cache_dir() -> Option<PathBuf> {
+    env::var_os(CACHE_DIR_ENV)
+        .map(PathBuf::from)
+        .or_else(|| dirs::cache_dir().map(|dir| dir.join("romanize")))
+}
+
+/// A zip archive containing the files of an igo dictionary.
+///
+/// By default the archive is extracted into a cache directory once so later runs only have to
+/// load it. It can also be loaded directly from memory without writing anything to the file
+/// system (e.g. on a read-only file system) with
+/// [`RomanizerBuilder::disable_cache`](::RomanizerBuilder::disable_cache) or
+/// [`IgoTokenizer::from_archive`](::IgoTokenizer::from_archive), at the cost of unpacking it on
+/// every start.
+///
+/// # Examples
+///
+#[cfg_attr(
+    not(any(feature = "bundled-ipadic", feature = "bundled-ipadic-xz")),
+    doc = "```ignore"
+)]
+#[cfg_attr(
+    any(feature = "bundled-ipadic", feature = "bundled-ipadic-xz"),
+    doc = "```no_run"
+)]
+/// use romanize::{DictionaryArchive, Romanizer};
+///
+/// // E.g. while building a container image
+/// DictionaryArchive::bundled().extract_to_cache("/opt/romanize").unwrap();
+///
+/// // At runtime; only reads from /opt/romanize
+/// let romanizer = Romanizer::builder().cache_dir("/opt/romanize").build().unwrap();
+///
+/// // Or without any files at all
+/// let romanizer = Romanizer::builder().disable_cache().build().unwrap();
+/// ```
+#[derive(Clone)]
+pub struct DictionaryArchive {
+    bytes: Cow<'static, [u8]>,
+    compression: Compression,
+}
+
+/// Compression applied on top of the zip archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    None,
+    #[cfg(feature = "xz2")]
+    Xz,
+}
+
+/// Where the tagger loads the dictionary from.
+pub(crate) enum DictionarySource {
+    /// A directory containing the files of the dictionary.
+    Dir(PathBuf),
+    /// The contents of the files by name.
+    Files(HashMap<String, Vec<u8>>),
+}
+
+/// Time spent loading the dictionary while building a [`Romanizer`](::Romanizer).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadStats {
+    /// Time spent decompressing the archive. `None` if it isn't compressed with an additional
+    /// codec or didn't have to be extracted.
+    pub decompression: Option<Duration>,
+    /// Time spent extracting the archive (or unpacking it in memory). `None` if it was already
+    /// extracted to the cache or a dictionary directory was used.
+    pub extraction: Option<Duration>,
+    /// Time spent loading the dictionary into the tagger.
+    pub loading: Duration,
+}
+
+impl DictionaryArchive {
+    /// The IPADIC archive bundled with this crate. Requires the `bundled-ipadic` or the
+    /// `bundled-ipadic-xz` feature; the latter takes precedence if both are enabled.
+    #[cfg(feature = "bundled-ipadic-xz")]
+    pub fn bundled() -> DictionaryArchive {
+        DictionaryArchive::from_xz_bytes(
+            &include_bytes!(concat!(env!("OUT_DIR"), "/ipadic.zip.xz"))[..],
+        )
+    }
+
+    /// The IPADIC archive bundled with this crate. Requires the `bundled-ipadic` or the
+    /// `bundled-ipadic-xz` feature; the latter takes precedence if both are enabled.
+    #[cfg(all(feature = "bundled-ipadic", not(feature = "bundled-ipadic-xz")))]
+    pub fn bundled() -> DictionaryArchive {
+        DictionaryArchive::from_bytes(&include_bytes!("../ipadic/ipadic.zip")[..])
+    }
+
+    /// A caller supplied archive, e.g. one embedded with `include_bytes!`. The files of the
+    /// dictionary may be in a subdirectory of the archive.
+    pub fn from_bytes<B: Into<Cow<'static, [u8]>>>(bytes: B) -> DictionaryArchive {
+        DictionaryArchive {
+            bytes: bytes.into(),
+            compression: Compression::None,
+        }
+    }
+
+    /// Like [`DictionaryArchive::from_bytes`] but for a zip archive which has been compressed
+    /// with xz (e.g. `zip -0` followed by `xz -9e`). Requires the `xz2` feature.
+    #[cfg(feature = "xz2")]
+    pub fn from_xz_bytes<B: Into<Cow<'static, [u8]>>>(bytes: B) -> DictionaryArchive {
+        DictionaryArchive {
+            bytes: bytes.into(),
+            compression: Compression::Xz,
+        }
+    }
+
+    /// Extract the archive into `cache_dir` unless that was already done (e.g. by a previous run)
+    /// and return the path of the extracted dictionary. Nothing is written if the dictionary is
+    /// already present.
+    ///
+    /// The dictionary is stored in a subdirectory named after the checksum of the archive so a
+    /// changed archive is extracted again.
+    pub fn extract_to_cache<P: AsRef<Path>>(&self, cache_dir: P) -> Result<PathBuf, RomanizeError> {
+        self.extract_to_cache_with_stats(cache_dir.as_ref(), &mut LoadStats::default())
+    }
+
+    fn extract_to_cache_with_stats(
+        &self,
+        cache_dir: &Path,
+        stats: &mut LoadStats,
+    ) -> Result<PathBuf, RomanizeError> {
+        let path = cache_dir.join(format!("dictionary-{:08x}", crc32fast::hash(&self.bytes)));
+        if path.is_dir() {
+            return Ok(path);
+        }
+
+        // Extract into a temporary directory first and move it in place afterwards so other
+        // processes never see a partially extracted dictionary
+        fs::create_dir_all(cache_dir)?;
+        let tempdir = TempDir::new_in(cache_dir)?;
+        self.unpack(tempdir.path(), stats)?;
+        match fs::rename(tempdir.path(), &path) {
+            Ok(()) => {}
+            // Another process was faster
+            Err(_) if path.is_dir() => {}
+            Err(e) => return Err(e.into()),
+        }
+        // Dropping `tempdir` after the rename is fine as errors during its removal are ignored
+
+        Ok(path)
+    }
+
+    /// Extract the archive into `cache_dir` or, if that isn't set, the [`default_cache_dir`]. If
+    /// the default cache directory can't be used or `use_cache` is false, the archive is unpacked
+    /// in memory instead so nothing is written.
+    pub(crate) fn load(
+        &self,
+        cache_dir: Option<&Path>,
+        use_cache: bool,
+        stats: &mut LoadStats,
+    ) -> Result<DictionarySource, RomanizeError> {
+        if use_cache {
+            if let Some(cache_dir) = cache_dir {
+                let path = self.extract_to_cache_with_stats(cache_dir, stats)?;
+                return Ok(DictionarySource::Dir(path));
+            }
+            if let Some(path) = default_cache_dir()
+                .and_then(|cache_dir| self.extract_to_cache_with_stats(&cache_dir, stats).ok())
+            {
+                return Ok(DictionarySource::Dir(path));
+            }
+        }
+
+        // Don't report the time of a failed attempt to use the default cache directory
+        *stats = LoadStats::default();
+        Ok(DictionarySource::Files(self.read_files(stats)?))
+    }
+
+    /// Decompress (if needed) and unzip the archive into `output_directory`.
+    fn unpack(&self, output_directory: &Path, stats: &mut LoadStats) -> Result<(), RomanizeError> {
+        let (zip, decompression) = self.decompress()?;
+        stats.decompression = decompression;
+        let start = Instant::now();
+        for_each_file(&zip, |name, file| {
+            let mut outfile = File::create(output_directory.join(name))?;
+            io::copy(file, &mut outfile)?;
+            Ok(())
+        })?;
+        stats.extraction = Some(start.elapsed());
+
+        Ok(())
+    }
+
+    /// Decompress (if needed) and unzip the archive in memory, returning the contents of its
+    /// files by name.
+    pub(crate) fn read_files(
+        &self,
+        stats: &mut LoadStats,
+    ) -> Result<HashMap<String, Vec<u8>>, RomanizeError> {
+        let (zip, decompression) = self.decompress()?;
+        stats.decompression = decompression;
+        let start = Instant::now();
+        let mut files = HashMap::new();
+        for_each_file(&zip, |name, file| {
+            let mut contents = Vec::with_capacity(file.size() as usize);
+            file.read_to_end(&mut contents)?;
+            files.insert(name.to_string(), contents);
+            Ok(())
+        })?;
+        stats.extraction = Some(start.elapsed());
+
+        Ok(files)
+    }
+
+    /// The zip archive and the time spent decompressing it, if needed.
+    fn decompress<'a>(&'a self) -> Result<(Cow<'a, [u8]>, Option<Duration>), RomanizeError> {
+        match self.compression {
+            Compression::None => Ok((Cow::Borrowed(&self.bytes[..]), None)),
+            #[cfg(feature = "xz2")]
+            Compression::Xz => {
+                let start = Instant::now();
+                let mut zip = Vec::new();
+                XzDecoder::new(&self.bytes[..]).read_to_end(&mut zip)?;
+                Ok((Cow::Owned(zip), Some(start.elapsed())))
+            }
+        }
+    }
+}
+
+impl fmt::Debug for DictionaryArchive {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DictionaryArchive")
+            .field("len", &self.bytes.len())
+            .field("compression", &self.compression)
+            .finish()
+    }
+}
+
+/// The files making up an igo dictionary.
+pub(crate) const DICTIONARY_FILES: &[&str] = &[
+    "char.category",
+    "code2category",
+    "matrix.bin",
+    "word.ary.idx",
+    "word.dat",
+    "word.inf",
+    "word2id",
+];
+
+/// Check that `dir` contains an igo dictionary before handing it to the tagger so the error can
+/// name the offending file.
+pub fn check_dictionary_dir(dir: &Path) -> Result<(), RomanizeError> {
+    for file in DICTIONARY_FILES {
+        let path = dir.join(file);
+        let metadata = match fs::metadata(&path) {
+            Ok(ref metadata) if metadata.is_file() => metadata.clone(),
+            _ => return Err(RomanizeError::MissingDictionaryFile(path)),
+        };
+        let len = metadata.len();
+        let malformed =
+            |reason: String| Err(RomanizeError::MalformedDictionaryFile(path.clone(), reason));
+
+        match *file {
+            // Category and compatibility mask (two i32s) for every UTF-16 code unit
+            "code2category" if len != 0x10000 * 8 => {
+                return malformed(format!("expected {} bytes, found {}", 0x10000 * 8, len));
+            }
+            // Four i32s per category
+            "char.category" if len == 0 || len % 16 != 0 => {
+                return malformed(format!("unexpected size of {} bytes", len));
+            }
+            // Two i32 dimensions followed by the i16 connection costs
+            "matrix.bin" => {
+                let mut header = [0; 8];
+                File::open(&path)?
+                    .read_exact(&mut header)
+                    .or_else(|_| malformed("missing header".to_string()))?;
+                let left = u64::from(u32::from_le_bytes([
+                    header[0], header[1], header[2], header[3],
+                ]));
+                let right = u64::from(u32::from_le_bytes([
+                    header[4], header[5], header[6], header[7],
+                ]));
+                if len != 8 + left * right * 2 {
+                    return malformed(format!(
+                        "expected {} bytes for a {}x{} matrix, found {}",
+                        8 + left * right * 2,
+                        left,
+                        right,
+                        len
+                    ));
+                }
+            }
+            _ if len == 0 => return malformed("file is empty".to_string()),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Call `f` with the name and contents of every file (not directory) of the zip archive. Files in
+/// subdirectories are treated as if they were at the top (suffices for our use case).
+fn for_each_file<F>(zip: &[u8], mut f: F) -> Result<(), RomanizeError>
+where
+    F: FnMut(&str, &mut ZipFile) -> Result<(), RomanizeError>,
+{
+    let invalid =
+        |e: String| RomanizeError::Dictionary(format!("invalid dictionary archive: {}", e));
+    let mut archive = ZipArchive::new(Cursor::new(zip)).map_err(|e| invalid(e.to_string()))?;
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).map_err(|e| invalid(e.to_string()))?;
+        if file.name().ends_with('/') {
+            continue;
+        }
+        let name = match file.sanitized_name().file_name().and_then(OsStr::to_str) {
+            Some(name) => name.to_string(),
+            None => return Err(invalid(format!("invalid file name \"{}\"", file.name()))),
+        };
+        f(&name, &mut file)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::{FileOptions, ZipWriter};
+
+    #[test]
+    fn invalid_file_name() {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("..", FileOptions::default()).unwrap();
+        zip.write_all(b"data").unwrap();
+        let archive = DictionaryArchive::from_bytes(zip.finish().unwrap().into_inner());
+
+        match archive.read_files(&mut LoadStats::default()) {
+            Err(RomanizeError::Dictionary(reason)) => assert!(reason.contains("\"..\"")),
+            _ => panic!("expected an invalid file name"),
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Errors which can occur while loading the dictionary or romanizing text.
+#[derive(Debug)]
+pub enum RomanizeError {
+    /// Extracting or reading the dictionary failed.
+    Io(io::Error),
+    /// The tagger couldn't load the dictionary.
+    Dictionary(String),
+    /// No dictionary was configured and none is bundled as the `bundled-ipadic` feature is
+    /// disabled.
+    NoDictionary,
+    /// A file of the dictionary doesn't exist.
+    MissingDictionaryFile(PathBuf),
+    /// A file of the dictionary exists but isn't valid.
+    MalformedDictionaryFile(PathBuf, String),
+    /// A word found by the tagger couldn't be located in the input.
+    SurfaceNotFound(String),
+    /// An entry of a [`UserDictionary`](::UserDictionary) is invalid. Contains the line number
+    /// (0 for entries added with [`UserDictionary::add`](::UserDictionary::add)) and the reason.
+    InvalidUserDictionary(usize, String),
+    /// [`Overrides`](::Overrides) couldn't be parsed.
+    InvalidOverrides(String),
+}
+
+impl fmt::Display for RomanizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RomanizeError::Io(ref e) => write!(f, "I/O error: {}", e),
+            RomanizeError::Dictionary(ref e) => write!(f, "failed to load dictionary: {}", e),
+            RomanizeError::NoDictionary => write!(
+                f,
+                "no dictionary configured and the `bundled-ipadic` feature is disabled"
+            ),
+            RomanizeError::MissingDictionaryFile(ref path) => {
+                write!(f, "missing dictionary file {}", path.display())
+            }
+            RomanizeError::MalformedDictionaryFile(ref path, ref reason) => {
+                write!(
+                    f,
+                    "malformed dictionary file {}: {}",
+                    path.display(),
+                    reason
+                )
+            }
+            RomanizeError::SurfaceNotFound(ref surface) => {
+                write!(f, "couldn't find \"{}\" in the input", surface)
+            }
+            RomanizeError::InvalidUserDictionary(0, ref reason) => {
+                write!(f, "invalid user dictionary entry: {}", reason)
+            }
+            RomanizeError::InvalidUserDictionary(line, ref reason) => {
+                write!(
+                    f,
+                    "invalid user dictionary entry on line {}: {}",
+                    line, reason
+                )
+            }
+            RomanizeError::InvalidOverrides(ref reason) => {
+                write!(f, "invalid overrides: {}", reason)
+            }
+        }
+    }
+}
+
+impl Error for RomanizeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            RomanizeError::Io(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RomanizeError {
+    fn from(e: io::Error) -> RomanizeError {
+        RomanizeError::Io(e)
+    }
+}
@@ -0,0 +1,690 @@
+/// The romanization system used to spell out kana readings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RomanizationSystem {
+    /// Modified (revised) Hepburn as used by most dictionaries: `ō`, `shi`, `n'` before vowels.
+    #[default]
+    ModifiedHepburn,
+    /// Traditional Hepburn: `m` before labials, `n-` before vowels and `wo` for ヲ.
+    TraditionalHepburn,
+    /// Hepburn as used in Japanese passports: no long vowel marks and `m` before labials.
+    PassportHepburn,
+    /// Kunrei-shiki (ISO 3602): `si`, `ti`, `tu`, `hu`, `zya` and circumflexed long vowels.
+    KunreiShiki,
+    /// Nihon-shiki: like Kunrei-shiki but keeps ヂ/ヅ as `di`/`du` and ヲ as `wo`.
+    NihonShiki,
+    /// ISO 3602 Strict, the Nihon-shiki based variant of the standard.
+    Iso3602Strict,
+}
+
+/// How long vowels are written in the romanized output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LongVowelStyle {
+    /// `Tōkyō`
+    Macron,
+    /// `Tôkyô`
+    Circumflex,
+    /// `Tookyoo`
+    Doubled,
+    /// `Toukyou`; spells out the kana as written. Long vowels marked with ー are doubled.
+    KanaFaithful,
+    /// `Tokyo`
+    Omitted,
+}
+
+/// How a syllabic n (ン) is separated from a following vowel or y, distinguishing e.g. げんいん
+/// from げにん.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyllabicNStyle {
+    /// `gen'in`, `tan'i`
+    Apostrophe,
+    /// `gen-in`, `tan-i`
+    Hyphen,
+    /// `genin`, `tani`
+    Omitted,
+}
+
+impl SyllabicNStyle {
+    /// What is put between the n and the vowel or y.
+    pub fn separator(self) -> &'static str {
+        match self {
+            SyllabicNStyle::Apostrophe => "'",
+            SyllabicNStyle::Hyphen => "-",
+            SyllabicNStyle::Omitted => "",
+        }
+    }
+}
+
+/// How a sokuon (ッ) is written if it isn't followed by a consonant it could double, e.g. at the
+/// end of an exclamation like あっ.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SokuonStyle {
+    /// A hyphen and the syllable ツ of the system: `a-tsu`.
+    Tsu,
+    /// A glottal stop written as an apostrophe: `a'`.
+    #[default]
+    Apostrophe,
+    /// Nothing: `a`.
+    Omitted,
+}
+
+impl RomanizationSystem {
+    /// The [`SyllabicNStyle`] conventionally used with this system.
+    pub fn syllabic_n_style(self) -> SyllabicNStyle {
+        match self {
+            RomanizationSystem::TraditionalHepburn => SyllabicNStyle::Hyphen,
+            RomanizationSystem::PassportHepburn => SyllabicNStyle::Omitted,
+            _ => SyllabicNStyle::Apostrophe,
+        }
+    }
+
+    /// The [`LongVowelStyle`] conventionally used with this system.
+    pub fn long_vowel_style(self) -> LongVowelStyle {
+        match self {
+            RomanizationSystem::ModifiedHepburn | RomanizationSystem::TraditionalHepburn => {
+                LongVowelStyle::Macron
+            }
+            RomanizationSystem::PassportHepburn => LongVowelStyle::Omitted,
+            _ => LongVowelStyle::Circumflex,
+        }
+    }
+
+    fn is_hepburn(self) -> bool {
+        matches!(
+            self,
+            RomanizationSystem::ModifiedHepburn
+                | RomanizationSystem::TraditionalHepburn
+                | RomanizationSystem::PassportHepburn
+        )
+    }
+
+    fn is_nihon(self) -> bool {
+        matches!(
+            self,
+            RomanizationSystem::NihonShiki | RomanizationSystem::Iso3602Strict
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Unit {
+    Syllable(&'static str),
+    SyllabicN,
+    Sokuon,
+    LongMark,
+    Other(char),
+}
+
+/// Romanize a kana string (hiragana or katakana) with the given [`RomanizationSystem`].
+///
+/// Characters which aren't kana are passed through unchanged. A sokuon which doesn't double a
+/// consonant, e.g. in アッ, is written as an apostrophe (`a'`).
+///
+/// # Examples
+///
+/// ```
+/// use romanize::{kana_to_romaji, RomanizationSystem};
+///
+/// assert_eq!(kana_to_romaji("トーキョー", RomanizationSystem::ModifiedHepburn), "tōkyō");
+/// assert_eq!(kana_to_romaji("しんぶん", RomanizationSystem::KunreiShiki), "sinbun");
+/// ```
+pub fn kana_to_romaji(kana: &str, system: RomanizationSystem) -> String {
+    kana_to_romaji_with(kana, system, system.long_vowel_style())
+}
+
+/// Like [`kana_to_romaji`] but with an explicit [`LongVowelStyle`] instead of the one
+/// conventionally used with `system`.
+///
+/// Only long vowels marked with ー are affected; vowels spelled out in kana (e.g. トウキョウ) are
+/// kept as written. Pass the pronunciation (トーキョー) to have them treated as long vowels.
+///
+/// # Examples
+///
+/// ```
+/// use romanize::{kana_to_romaji_with, LongVowelStyle, RomanizationSystem};
+///
+/// let system = RomanizationSystem::ModifiedHepburn;
+/// assert_eq!(kana_to_romaji_with("トーキョー", system, LongVowelStyle::Doubled), "tookyoo");
+/// assert_eq!(kana_to_romaji_with("トーキョー", system, LongVowelStyle::Omitted), "tokyo");
+/// ```
+pub fn kana_to_romaji_with(
+    kana: &str,
+    system: RomanizationSystem,
+    long_vowels: LongVowelStyle,
+) -> String {
+    transliterate(
+        kana,
+        system,
+        long_vowels,
+        system.syllabic_n_style(),
+        SokuonStyle::default(),
+    )
+}
+
+/// Like [`kana_to_romaji_with`] but also with an explicit [`SyllabicNStyle`] and [`SokuonStyle`].
+pub(crate) fn transliterate(
+    kana: &str,
+    system: RomanizationSystem,
+    long_vowels: LongVowelStyle,
+    syllabic_n: SyllabicNStyle,
+    sokuon: SokuonStyle,
+) -> String {
+    let units = split_units(kana, system);
+    let mut romaji = String::with_capacity(kana.len());
+
+    let mut pending_sokuon = false;
+    for (i, unit) in units.iter().enumerate() {
+        // A sokuon doubles the consonant of the following syllable and is written out otherwise,
+        // e.g. before a vowel, ン or ー
+        if pending_sokuon {
+            match *unit {
+                Unit::Syllable(syllable) if !syllable.starts_with(is_vowel) => {
+                    push_geminate(&mut romaji, syllable, system)
+                }
+                _ => push_sokuon(&mut romaji, system, sokuon),
+            }
+            pending_sokuon = false;
+        }
+
+        let next = units.get(i + 1);
+        match *unit {
+            Unit::Syllable(syllable) => romaji.push_str(syllable),
+            Unit::SyllabicN => push_syllabic_n(&mut romaji, next, system, syllabic_n),
+            Unit::Sokuon => pending_sokuon = true,
+            Unit::LongMark => lengthen_last_vowel(&mut romaji, long_vowels),
+            Unit::Other(c) => romaji.push(c),
+        }
+    }
+    if pending_sokuon {
+        push_sokuon(&mut romaji, system, sokuon);
+    }
+
+    romaji
+}
+
+/// Mark the long vowels spelled out in a reading with ー where the pronunciation has ー at the same
+/// position, i.e. a ウ after an o or u sound (トウキョウ pronounced トーキョー becomes トーキョー).
+/// Everything else is kept as written, e.g. the オオ of オオキイ, the ei of センセイ or the ウ of
+/// ミズウミ which starts another morpheme. Without a pronunciation of the same length the reading
+/// is returned as is.
+pub(crate) fn mark_long_vowels(reading: &str, pronunciation: Option<&str>) -> String {
+    let chars = reading.chars().map(to_katakana).collect::<Vec<_>>();
+    let pronunciation = pronunciation
+        .map(|pronunciation| pronunciation.chars().collect::<Vec<_>>())
+        .filter(|pronunciation| pronunciation.len() == chars.len())
+        .unwrap_or_default();
+
+    let mut marked = String::with_capacity(reading.len());
+    for (i, &c) in chars.iter().enumerate() {
+        let previous = if i > 0 { vowel(chars[i - 1]) } else { None };
+        let long = c == 'ウ'
+            && (previous == Some('o') || previous == Some('u'))
+            && pronunciation.get(i) == Some(&'ー');
+        marked.push(if long { 'ー' } else { c });
+    }
+    marked
+}
+
+/// The vowel a katakana character ends with.
+fn vowel(c: char) -> Option<char> {
+    let mut buf = [0; 4];
+    syllable(c.encode_utf8(&mut buf), RomanizationSystem::ModifiedHepburn)
+        .and_then(|syllable| syllable.chars().last())
+        .filter(|&c| is_vowel(c))
+}
+
+fn split_units(kana: &str, system: RomanizationSystem) -> Vec<Unit> {
+    let chars = kana.chars().map(to_katakana).collect::<Vec<_>>();
+    let mut units = Vec::with_capacity(chars.len());
+
+    let mut i = 0;
+    let mut buf = [0; 8];
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            'ン' => units.push(Unit::SyllabicN),
+            'ッ' => units.push(Unit::Sokuon),
+            'ー' => units.push(Unit::LongMark),
+            _ => {
+                // Prefer digraphs such as キャ or ティ over the single character
+                if let Some(&small) = chars.get(i + 1) {
+                    let mut digraph = String::with_capacity(6);
+                    digraph.push(c);
+                    digraph.push(small);
+                    if let Some(syllable) = syllable(&digraph, system) {
+                        units.push(Unit::Syllable(syllable));
+                        i += 2;
+                        continue;
+                    }
+                }
+                match syllable(c.encode_utf8(&mut buf), system) {
+                    Some(syllable) => units.push(Unit::Syllable(syllable)),
+                    None => units.push(Unit::Other(c)),
+                }
+            }
+        }
+        i += 1;
+    }
+
+    units
+}
+
+fn to_katakana(c: char) -> char {
+    match c {
+        'ぁ'..='ゖ' | 'ゝ' | 'ゞ' => ::std::char::from_u32(c as u32 + 0x60).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// Double the consonant `syllable` starts with for a preceding sokuon, e.g. the t of `chotto`.
+pub(crate) fn push_geminate(romaji: &mut String, syllable: &str, system: RomanizationSystem) {
+    if system.is_hepburn() && syllable.starts_with("ch") {
+        romaji.push('t');
+    } else if let Some(c) = syllable.chars().next() {
+        if !is_vowel(c) {
+            romaji.push(c);
+        }
+    }
+}
+
+/// Write a sokuon which doesn't double a consonant.
+fn push_sokuon(romaji: &mut String, system: RomanizationSystem, style: SokuonStyle) {
+    match style {
+        SokuonStyle::Tsu => {
+            romaji.push('-');
+            romaji.push_str(syllable("ツ", system).unwrap_or("tsu"));
+        }
+        SokuonStyle::Apostrophe => romaji.push('\''),
+        SokuonStyle::Omitted => {}
+    }
+}
+
+fn push_syllabic_n(
+    romaji: &mut String,
+    next: Option<&Unit>,
+    system: RomanizationSystem,
+    style: SyllabicNStyle,
+) {
+    let next = match next {
+        Some(&Unit::Syllable(syllable)) => syllable.chars().next(),
+        _ => None,
+    };
+    match (next, system) {
+        (Some('b'), RomanizationSystem::TraditionalHepburn)
+        | (Some('m'), RomanizationSystem::TraditionalHepburn)
+        | (Some('p'), RomanizationSystem::TraditionalHepburn)
+        | (Some('b'), RomanizationSystem::PassportHepburn)
+        | (Some('m'), RomanizationSystem::PassportHepburn)
+        | (Some('p'), RomanizationSystem::PassportHepburn) => romaji.push('m'),
+        (Some(c), _) if is_vowel(c) || c == 'y' => {
+            romaji.push('n');
+            romaji.push_str(style.separator());
+        }
+        _ => romaji.push('n'),
+    }
+}
+
+/// Whether the romanized `romaji` starts with a vowel or y, which has to be separated from a
+/// preceding syllabic n.
+pub(crate) fn starts_with_vowel_or_y(romaji: &str) -> bool {
+    romaji
+        .chars()
+        .next()
+        .and_then(|c| c.to_lowercase().next())
+        .map(|c| c == 'y' || is_vowel(c) || "āīūēōâîûêô".contains(c))
+        .unwrap_or(false)
+}
+
+fn lengthen_last_vowel(romaji: &mut String, style: LongVowelStyle) {
+    let last = romaji.pop();
+    match last {
+        Some(vowel) if is_vowel(vowel) => match style {
+            LongVowelStyle::Macron => romaji.push(with_macron(vowel)),
+            LongVowelStyle::Circumflex => romaji.push(with_circumflex(vowel)),
+            LongVowelStyle::Doubled | LongVowelStyle::KanaFaithful => {
+                romaji.push(vowel);
+                romaji.push(vowel);
+            }
+            LongVowelStyle::Omitted => romaji.push(vowel),
+        },
+        Some(c) => {
+            romaji.push(c);
+            romaji.push('-');
+        }
+        None => romaji.push('-'),
+    }
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'i' | 'u' | 'e' | 'o')
+}
+
+fn with_macron(vowel: char) -> char {
+    match vowel {
+        'a' => 'ā',
+        'i' => 'ī',
+        'u' => 'ū',
+        'e' => 'ē',
+        'o' => 'ō',
+        c => c,
+    }
+}
+
+fn with_circumflex(vowel: char) -> char {
+    match vowel {
+        'a' => 'â',
+        'i' => 'î',
+        'u' => 'û',
+        'e' => 'ê',
+        'o' => 'ô',
+        c => c,
+    }
+}
+
+fn syllable(kana: &str, system: RomanizationSystem) -> Option<&'static str> {
+    let (hepburn, kunrei, nihon) = match kana {
+        "ア" | "ァ" => ("a", "a", "a"),
+        "イ" | "ィ" => ("i", "i", "i"),
+        "ウ" | "ゥ" => ("u", "u", "u"),
+        "エ" | "ェ" => ("e", "e", "e"),
+        "オ" | "ォ" => ("o", "o", "o"),
+        "カ" | "ヵ" => ("ka", "ka", "ka"),
+        "キ" => ("ki", "ki", "ki"),
+        "ク" => ("ku", "ku", "ku"),
+        "ケ" | "ヶ" => ("ke", "ke", "ke"),
+        "コ" => ("ko", "ko", "ko"),
+        "ガ" => ("ga", "ga", "ga"),
+        "ギ" => ("gi", "gi", "gi"),
+        "グ" => ("gu", "gu", "gu"),
+        "ゲ" => ("ge", "ge", "ge"),
+        "ゴ" => ("go", "go", "go"),
+        "サ" => ("sa", "sa", "sa"),
+        "シ" => ("shi", "si", "si"),
+        "ス" => ("su", "su", "su"),
+        "セ" => ("se", "se", "se"),
+        "ソ" => ("so", "so", "so"),
+        "ザ" => ("za", "za", "za"),
+        "ジ" => ("ji", "zi", "zi"),
+        "ズ" => ("zu", "zu", "zu"),
+        "ゼ" => ("ze", "ze", "ze"),
+        "ゾ" => ("zo", "zo", "zo"),
+        "タ" => ("ta", "ta", "ta"),
+        "チ" => ("chi", "ti", "ti"),
+        "ツ" => ("tsu", "tu", "tu"),
+        "テ" => ("te", "te", "te"),
+        "ト" => ("to", "to", "to"),
+        "ダ" => ("da", "da", "da"),
+        "ヂ" => ("ji", "zi", "di"),
+        "ヅ" => ("zu", "zu", "du"),
+        "デ" => ("de", "de", "de"),
+        "ド" => ("do", "do", "do"),
+        "ナ" => ("na", "na", "na"),
+        "ニ" => ("ni", "ni", "ni"),
+        "ヌ" => ("nu", "nu", "nu"),
+        "ネ" => ("ne", "ne", "ne"),
+        "ノ" => ("no", "no", "no"),
+        "ハ" => ("ha", "ha", "ha"),
+        "ヒ" => ("hi", "hi", "hi"),
+        "フ" => ("fu", "hu", "hu"),
+        "ヘ" => ("he", "he", "he"),
+        "ホ" => ("ho", "ho", "ho"),
+        "バ" => ("ba", "ba", "ba"),
+        "ビ" => ("bi", "bi", "bi"),
+        "ブ" => ("bu", "bu", "bu"),
+        "ベ" => ("be", "be", "be"),
+        "ボ" => ("bo", "bo", "bo"),
+        "パ" => ("pa", "pa", "pa"),
+        "ピ" => ("pi", "pi", "pi"),
+        "プ" => ("pu", "pu", "pu"),
+        "ペ" => ("pe", "pe", "pe"),
+        "ポ" => ("po", "po", "po"),
+        "マ" => ("ma", "ma", "ma"),
+        "ミ" => ("mi", "mi", "mi"),
+        "ム" => ("mu", "mu", "mu"),
+        "メ" => ("me", "me", "me"),
+        "モ" => ("mo", "mo", "mo"),
+        "ヤ" | "ャ" => ("ya", "ya", "ya"),
+        "ユ" | "ュ" => ("yu", "yu", "yu"),
+        "ヨ" | "ョ" => ("yo", "yo", "yo"),
+        "ラ" => ("ra", "ra", "ra"),
+        "リ" => ("ri", "ri", "ri"),
+        "ル" => ("ru", "ru", "ru"),
+        "レ" => ("re", "re", "re"),
+        "ロ" => ("ro", "ro", "ro"),
+        "ワ" | "ヮ" => ("wa", "wa", "wa"),
+        "ヰ" => ("i", "i", "wi"),
+        "ヱ" => ("e", "e", "we"),
+        "ヲ" => {
+            return Some(match system {
+                RomanizationSystem::TraditionalHepburn => "wo",
+                _ if system.is_nihon() => "wo",
+                _ => "o",
+            })
+        }
+        "ヴ" => ("vu", "vu", "vu"),
+
+        "キャ" => ("kya", "kya", "kya"),
+        "キュ" => ("kyu", "kyu", "kyu"),
+        "キョ" => ("kyo", "kyo", "kyo"),
+        "ギャ" => ("gya", "gya", "gya"),
+        "ギュ" => ("gyu", "gyu", "gyu"),
+        "ギョ" => ("gyo", "gyo", "gyo"),
+        "シャ" => ("sha", "sya", "sya"),
+        "シュ" => ("shu", "syu", "syu"),
+        "ショ" => ("sho", "syo", "syo"),
+        "ジャ" => ("ja", "zya", "zya"),
+        "ジュ" => ("ju", "zyu", "zyu"),
+        "ジョ" => ("jo", "zyo", "zyo"),
+        "チャ" => ("cha", "tya", "tya"),
+        "チュ" => ("chu", "tyu", "tyu"),
+        "チョ" => ("cho", "tyo", "tyo"),
+        "ヂャ" => ("ja", "zya", "dya"),
+        "ヂュ" => ("ju", "zyu", "dyu"),
+        "ヂョ" => ("jo", "zyo", "dyo"),
+        "ニャ" => ("nya", "nya", "nya"),
+        "ニュ" => ("nyu", "nyu", "nyu"),
+        "ニョ" => ("nyo", "nyo", "nyo"),
+        "ヒャ" => ("hya", "hya", "hya"),
+        "ヒュ" => ("hyu", "hyu", "hyu"),
+        "ヒョ" => ("hyo", "hyo", "hyo"),
+        "ビャ" => ("bya", "bya", "bya"),
+        "ビュ" => ("byu", "byu", "byu"),
+        "ビョ" => ("byo", "byo", "byo"),
+        "ピャ" => ("pya", "pya", "pya"),
+        "ピュ" => ("pyu", "pyu", "pyu"),
+        "ピョ" => ("pyo", "pyo", "pyo"),
+        "ミャ" => ("mya", "mya", "mya"),
+        "ミュ" => ("myu", "myu", "myu"),
+        "ミョ" => ("myo", "myo", "myo"),
+        "リャ" => ("rya", "rya", "rya"),
+        "リュ" => ("ryu", "ryu", "ryu"),
+        "リョ" => ("ryo", "ryo", "ryo"),
+        "クヮ" => ("kwa", "kwa", "kwa"),
+        "グヮ" => ("gwa", "gwa", "gwa"),
+
+        // Extended katakana for loanwords; the Kunrei-shiki and Nihon-shiki standards don't cover
+        // most of these so they use the common Hepburn spelling
+        "イェ" => ("ye", "ye", "ye"),
+        "ウィ" => ("wi", "wi", "wi"),
+        "ウェ" => ("we", "we", "we"),
+        "ウォ" => ("wo", "wo", "wo"),
+        "ヴァ" => ("va", "va", "va"),
+        "ヴィ" => ("vi", "vi", "vi"),
+        "ヴェ" => ("ve", "ve", "ve"),
+        "ヴォ" => ("vo", "vo", "vo"),
+        "ヴュ" => ("vyu", "vyu", "vyu"),
+        "クァ" => ("kwa", "kwa", "kwa"),
+        "グァ" => ("gwa", "gwa", "gwa"),
+        "シェ" => ("she", "sye", "sye"),
+        "ジェ" => ("je", "zye", "zye"),
+        "チェ" => ("che", "tye", "tye"),
+        "ツァ" => ("tsa", "tsa", "tsa"),
+        "ツィ" => ("tsi", "tsi", "tsi"),
+        "ツェ" => ("tse", "tse", "tse"),
+        "ツォ" => ("tso", "tso", "tso"),
+        "ティ" => ("ti", "ti", "ti"),
+        "トゥ" => ("tu", "tu", "tu"),
+        "テュ" => ("tyu", "tyu", "tyu"),
+        "ディ" => ("di", "di", "di"),
+        "ドゥ" => ("du", "du", "du"),
+        "デュ" => ("dyu", "dyu", "dyu"),
+        "ファ" => ("fa", "fa", "fa"),
+        "フィ" => ("fi", "fi", "fi"),
+        "フェ" => ("fe", "fe", "fe"),
+        "フォ" => ("fo", "fo", "fo"),
+        "フュ" => ("fyu", "fyu", "fyu"),
+        _ => return None,
+    };
+
+    Some(if system.is_hepburn() {
+        hepburn
+    } else if system.is_nihon() {
+        nihon
+    } else {
+        kunrei
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn systems() {
+        use self::RomanizationSystem::*;
+
+        let cases = [
+            (
+                "シンブン",
+                [
+                    "shinbun", "shimbun", "shimbun", "sinbun", "sinbun", "sinbun",
+                ],
+            ),
+            (
+                "チョット",
+                ["chotto", "chotto", "chotto", "tyotto", "tyotto", "tyotto"],
+            ),
+            (
+                "ツヅキ",
+                [
+                    "tsuzuki", "tsuzuki", "tsuzuki", "tuzuki", "tuduki", "tuduki",
+                ],
+            ),
+            (
+                "ゲンイン",
+                ["gen'in", "gen-in", "genin", "gen'in", "gen'in", "gen'in"],
+            ),
+            (
+                "トーキョー",
+                ["tōkyō", "tōkyō", "tokyo", "tôkyô", "tôkyô", "tôkyô"],
+            ),
+            ("ヲ", ["o", "wo", "o", "o", "wo", "wo"]),
+        ];
+        let systems = [
+            ModifiedHepburn,
+            TraditionalHepburn,
+            PassportHepburn,
+            KunreiShiki,
+            NihonShiki,
+            Iso3602Strict,
+        ];
+
+        for &(kana, ref expected) in &cases {
+            for (&system, &expected) in systems.iter().zip(expected.iter()) {
+                assert_eq!(kana_to_romaji(kana, system), expected, "{:?}", system);
+            }
+        }
+    }
+
+    #[test]
+    fn syllabic_n() {
+        let system = RomanizationSystem::ModifiedHepburn;
+        let romanize = |kana, style| {
+            transliterate(
+                kana,
+                system,
+                LongVowelStyle::Macron,
+                style,
+                SokuonStyle::default(),
+            )
+        };
+        assert_eq!(romanize("タンイ", SyllabicNStyle::Apostrophe), "tan'i");
+        assert_eq!(romanize("タンイ", SyllabicNStyle::Hyphen), "tan-i");
+        assert_eq!(romanize("タンイ", SyllabicNStyle::Omitted), "tani");
+        assert_eq!(romanize("コンヤ", SyllabicNStyle::Hyphen), "kon-ya");
+        assert!(starts_with_vowel_or_y("Ōsaka"));
+        assert!(!starts_with_vowel_or_y("nyū"));
+    }
+
+    #[test]
+    fn sokuon() {
+        let romanize = |kana, system, style| {
+            transliterate(
+                kana,
+                system,
+                LongVowelStyle::Macron,
+                SyllabicNStyle::Apostrophe,
+                style,
+            )
+        };
+        let hepburn = RomanizationSystem::ModifiedHepburn;
+        assert_eq!(romanize("アッ", hepburn, SokuonStyle::Apostrophe), "a'");
+        assert_eq!(romanize("アッ", hepburn, SokuonStyle::Omitted), "a");
+        assert_eq!(romanize("アッ", hepburn, SokuonStyle::Tsu), "a-tsu");
+        assert_eq!(
+            romanize("アッ", RomanizationSystem::KunreiShiki, SokuonStyle::Tsu),
+            "a-tu"
+        );
+        assert_eq!(
+            romanize("チョッ!", hepburn, SokuonStyle::Apostrophe),
+            "cho'!"
+        );
+        // Not followed by a consonant
+        assert_eq!(romanize("アッア", hepburn, SokuonStyle::Apostrophe), "a'a");
+        assert_eq!(romanize("アッン", hepburn, SokuonStyle::Apostrophe), "a'n");
+        assert_eq!(romanize("アッー", hepburn, SokuonStyle::Apostrophe), "a'-");
+        assert_eq!(romanize("アッンカ", hepburn, SokuonStyle::Omitted), "anka");
+        assert_eq!(romanize("アッカ", hepburn, SokuonStyle::Tsu), "akka");
+    }
+
+    #[test]
+    fn long_vowels() {
+        let system = RomanizationSystem::ModifiedHepburn;
+        let romanize = |kana, style| kana_to_romaji_with(kana, system, style);
+        assert_eq!(romanize("センセー", LongVowelStyle::Macron), "sensē");
+        assert_eq!(romanize("センセー", LongVowelStyle::Circumflex), "sensê");
+        assert_eq!(romanize("センセー", LongVowelStyle::Doubled), "sensee");
+        assert_eq!(romanize("センセイ", LongVowelStyle::KanaFaithful), "sensei");
+        assert_eq!(
+            romanize("ボールペン", LongVowelStyle::KanaFaithful),
+            "boorupen"
+        );
+        assert_eq!(romanize("ボールペン", LongVowelStyle::Omitted), "borupen");
+    }
+
+    #[test]
+    fn marked_long_vowels() {
+        let mark = |reading, pronunciation| mark_long_vowels(reading, Some(pronunciation));
+        assert_eq!(mark("トウキョウ", "トーキョー"), "トーキョー");
+        assert_eq!(mark("スウジ", "スージ"), "スージ");
+        assert_eq!(mark("おおきい", "オーキイ"), "オオキイ");
+        assert_eq!(mark("センセイ", "センセー"), "センセイ");
+        assert_eq!(mark("オモウ", "オモウ"), "オモウ");
+        // Vowels of different morphemes
+        assert_eq!(mark("ミズウミ", "ミズウミ"), "ミズウミ");
+        assert_eq!(mark("コウシ", "コウシ"), "コウシ");
+        // Not aligned
+        assert_eq!(mark("トウキョウ", "トーキョ"), "トウキョウ");
+        assert_eq!(mark_long_vowels("トウキョウ", None), "トウキョウ");
+    }
+
+    #[test]
+    fn loanwords() {
+        let system = RomanizationSystem::ModifiedHepburn;
+        assert_eq!(
+            kana_to_romaji("エブリデイワールド", system),
+            "eburideiwārudo"
+        );
+        assert_eq!(kana_to_romaji("ふでペン", system), "fudepen");
+        assert_eq!(kana_to_romaji("ボールペン", system), "bōrupen");
+    }
+}
@@ -1,169 +1,908 @@
+extern crate crc32fast;
+extern crate dirs;
+#[cfg(feature = "encoding_rs")]
+extern crate encoding_rs;
 extern crate hangeul;
-extern crate igo;
+#[cfg(feature = "serde_json")]
+extern crate serde_json;
 extern crate tempfile;
+#[cfg(feature = "toml")]
+extern crate toml;
 extern crate unicode_normalization;
 extern crate wana_kana;
+#[cfg(feature = "xz2")]
+extern crate xz2;
 extern crate zip;
 
-use std::fs::File;
-use std::io::{self, Cursor};
-use std::path::Path;
+#[cfg(feature = "compiler")]
+mod compiler;
+mod dictionary;
+mod error;
+mod kana;
+mod options;
+mod overrides;
+mod pos;
+mod tagger;
+mod token;
+mod tokenizer;
+mod transliterator;
+mod user_dictionary;
 
-use igo::Tagger;
-use tempfile::TempDir;
-use wana_kana::is_katakana::is_katakana;
-use wana_kana::to_romaji::to_romaji;
-use zip::ZipArchive;
+use std::borrow::Cow;
+use std::iter;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+use wana_kana::is_kana::is_kana;
 
 use unicode_normalization::UnicodeNormalization;
 
-pub struct Romanizer {
-    // Drop order is top to bottom
-    tagger: Tagger,
-    // Keep `tempdir` in this struct as the directory is deleted once the struct is dropped
-    _tempdir: TempDir,
+use dictionary::DictionarySource;
+
+#[cfg(feature = "compiler")]
+pub use compiler::compile_dictionary;
+pub use dictionary::{default_cache_dir, DictionaryArchive, LoadStats, CACHE_DIR_ENV};
+pub use error::RomanizeError;
+pub use kana::{
+    kana_to_romaji, kana_to_romaji_with, LongVowelStyle, RomanizationSystem, SokuonStyle,
+    SyllabicNStyle,
+};
+pub use options::{Capitalization, ReadingSource, RomanizeOptions, Separator, WordDivision};
+pub use overrides::Overrides;
+pub use pos::{
+    Dependency, NounKind, PartOfSpeech, ParticleKind, PrefixKind, ProperNounKind, SuffixKind,
+    SymbolKind,
+};
+pub use token::Token;
+pub use tokenizer::{DictionaryFormat, IgoTokenizer, Morpheme, Tokenizer};
+pub use transliterator::Transliterator;
+pub use user_dictionary::UserDictionary;
+
+/// Romanizes Japanese text using the morphological analyzer `T` to find the readings.
+pub struct Romanizer<T = IgoTokenizer> {
+    options: RomanizeOptions,
+    load_stats: LoadStats,
+    overrides: Overrides,
+    transliterator: Option<Arc<dyn Transliterator>>,
+    tokenizer: T,
 }
 
-impl Romanizer {
-    /// Initialize a new [`Romanizer`]. This takes some time as some dictionary data has to get
-    /// extracted to the file sytem and loaded.
-    pub fn new() -> Result<Romanizer, io::Error> {
-        let tempdir = TempDir::new()?;
-        unzip(include_bytes!("../ipadic/ipadic.zip"), tempdir.path())?;
+/// Builder for a [`Romanizer`] with non-default [`RomanizeOptions`].
+///
+/// # Examples
+///
+#[cfg_attr(
+    not(any(feature = "bundled-ipadic", feature = "bundled-ipadic-xz")),
+    doc = "```ignore"
+)]
+#[cfg_attr(
+    any(feature = "bundled-ipadic", feature = "bundled-ipadic-xz"),
+    doc = "```"
+)]
+/// use romanize::{Capitalization, LongVowelStyle, RomanizerBuilder};
+///
+/// let romanizer = RomanizerBuilder::new()
+///     .long_vowels(LongVowelStyle::Omitted)
+///     .capitalization(Capitalization::Lowercase)
+///     .build()
+///     .unwrap();
+/// assert_eq!(romanizer.romanize("太陽のKiss"), "taiyo no Kiss");
+/// ```
+#[derive(Debug, Clone)]
+pub struct RomanizerBuilder {
+    options: RomanizeOptions,
+    archive: Option<DictionaryArchive>,
+    dictionary_dir: Option<PathBuf>,
+    dictionary_format: DictionaryFormat,
+    cache_dir: Option<PathBuf>,
+    use_cache: bool,
+    user_dictionary: UserDictionary,
+    overrides: Overrides,
+    transliterator: Option<Arc<dyn Transliterator>>,
+}
 
-        let tagger = Tagger::new(&tempdir.path()).unwrap();
+impl Default for RomanizerBuilder {
+    fn default() -> RomanizerBuilder {
+        RomanizerBuilder {
+            options: RomanizeOptions::default(),
+            archive: bundled_archive(),
+            dictionary_dir: None,
+            dictionary_format: DictionaryFormat::Ipadic,
+            cache_dir: None,
+            use_cache: true,
+            user_dictionary: UserDictionary::new(),
+            overrides: Overrides::new(),
+            transliterator: None,
+        }
+    }
+}
+
+impl RomanizerBuilder {
+    pub fn new() -> RomanizerBuilder {
+        RomanizerBuilder::default()
+    }
+
+    /// Use another dictionary instead of the bundled IPADIC. Without the `bundled-ipadic` feature
+    /// either this or [`RomanizerBuilder::dictionary_dir`] has to be set.
+    pub fn archive(mut self, archive: DictionaryArchive) -> RomanizerBuilder {
+        self.archive = Some(archive);
+        self
+    }
+
+    /// Load an already extracted igo dictionary from `dictionary_dir` instead of extracting an
+    /// archive. Takes precedence over [`RomanizerBuilder::archive`].
+    pub fn dictionary_dir<P: Into<PathBuf>>(mut self, dictionary_dir: P) -> RomanizerBuilder {
+        self.dictionary_dir = Some(dictionary_dir.into());
+        self
+    }
+
+    /// The layout of the features of the dictionary set with [`RomanizerBuilder::archive`] or
+    /// [`RomanizerBuilder::dictionary_dir`], e.g. [`DictionaryFormat::Unidic`] to use UniDic.
+    /// Defaults to [`DictionaryFormat::Ipadic`].
+    pub fn dictionary_format(mut self, dictionary_format: DictionaryFormat) -> RomanizerBuilder {
+        self.dictionary_format = dictionary_format;
+        self
+    }
+
+    /// Extract the dictionary into (and load it from) the given directory instead of the
+    /// [`default_cache_dir`].
+    pub fn cache_dir<P: Into<PathBuf>>(mut self, cache_dir: P) -> RomanizerBuilder {
+        self.cache_dir = Some(cache_dir.into());
+        self.use_cache = true;
+        self
+    }
+
+    /// Don't cache the extracted dictionary; load it directly from memory instead without
+    /// writing anything to the file system.
+    pub fn disable_cache(mut self) -> RomanizerBuilder {
+        self.use_cache = false;
+        self
+    }
+
+    /// Recognize the words of `user_dictionary` in addition to the ones of the dictionary. Its
+    /// entries compete with the words of the dictionary, see [`UserDictionary`].
+    pub fn user_dictionary(mut self, user_dictionary: UserDictionary) -> RomanizerBuilder {
+        self.user_dictionary = user_dictionary;
+        self
+    }
+
+    /// Romanize the strings of `overrides` as given instead of tagging them.
+    pub fn overrides(mut self, overrides: Overrides) -> RomanizerBuilder {
+        self.overrides = overrides;
+        self
+    }
+
+    /// Romanize the readings with `transliterator` instead of the [`RomanizationSystem`] of the
+    /// options.
+    pub fn transliterator<T: Transliterator + 'static>(
+        mut self,
+        transliterator: T,
+    ) -> RomanizerBuilder {
+        self.transliterator = Some(Arc::new(transliterator));
+        self
+    }
+
+    /// Replace all options at once.
+    pub fn options(mut self, options: RomanizeOptions) -> RomanizerBuilder {
+        self.options = options;
+        self
+    }
+
+    /// See [`RomanizeOptions::system`].
+    pub fn system(mut self, system: RomanizationSystem) -> RomanizerBuilder {
+        self.options.system = system;
+        self
+    }
+
+    /// See [`RomanizeOptions::long_vowels`].
+    pub fn long_vowels(mut self, long_vowels: LongVowelStyle) -> RomanizerBuilder {
+        self.options.long_vowels = Some(long_vowels);
+        self
+    }
+
+    /// See [`RomanizeOptions::syllabic_n`].
+    pub fn syllabic_n(mut self, syllabic_n: SyllabicNStyle) -> RomanizerBuilder {
+        self.options.syllabic_n = Some(syllabic_n);
+        self
+    }
+
+    /// See [`RomanizeOptions::sokuon`].
+    pub fn sokuon(mut self, sokuon: SokuonStyle) -> RomanizerBuilder {
+        self.options.sokuon = sokuon;
+        self
+    }
+
+    /// See [`RomanizeOptions::reading_source`].
+    pub fn reading_source(mut self, reading_source: ReadingSource) -> RomanizerBuilder {
+        self.options.reading_source = reading_source;
+        self
+    }
+
+    /// See [`RomanizeOptions::capitalization`].
+    pub fn capitalization(mut self, capitalization: Capitalization) -> RomanizerBuilder {
+        self.options.capitalization = capitalization;
+        self
+    }
+
+    /// See [`RomanizeOptions::word_division`].
+    pub fn word_division(mut self, word_division: WordDivision) -> RomanizerBuilder {
+        self.options.word_division = word_division;
+        self
+    }
+
+    /// See [`RomanizeOptions::hyphenate_honorifics`].
+    pub fn hyphenate_honorifics(mut self, hyphenate_honorifics: bool) -> RomanizerBuilder {
+        self.options.hyphenate_honorifics = hyphenate_honorifics;
+        self
+    }
+
+    /// See [`RomanizeOptions::hyphenate_compounds`].
+    pub fn hyphenate_compounds(mut self, hyphenate_compounds: bool) -> RomanizerBuilder {
+        self.options.hyphenate_compounds = hyphenate_compounds;
+        self
+    }
+
+    /// See [`RomanizeOptions::separator`].
+    pub fn separator(mut self, separator: Separator) -> RomanizerBuilder {
+        self.options.separator = separator;
+        self
+    }
+
+    /// See [`RomanizeOptions::normalize`].
+    pub fn normalize(mut self, normalize: bool) -> RomanizerBuilder {
+        self.options.normalize = normalize;
+        self
+    }
+
+    /// Build the [`Romanizer`]. The first build takes some time as the dictionary has to get
+    /// extracted to the cache directory; later ones only have to load it.
+    ///
+    /// If no cache directory was set explicitly and the default one can't be used, the dictionary
+    /// is loaded from memory instead.
+    pub fn build(self) -> Result<Romanizer, RomanizeError> {
+        let mut load_stats = LoadStats::default();
+        let source = match (self.dictionary_dir, self.archive) {
+            (Some(dictionary_dir), _) => {
+                dictionary::check_dictionary_dir(&dictionary_dir)?;
+                DictionarySource::Dir(dictionary_dir)
+            }
+            (None, Some(archive)) => {
+                archive.load(self.cache_dir.as_deref(), self.use_cache, &mut load_stats)?
+            }
+            (None, None) => return Err(RomanizeError::NoDictionary),
+        };
+
+        let start = Instant::now();
+        let mut tokenizer = IgoTokenizer::load(&source, self.dictionary_format)?;
+        tokenizer.add_user_dictionary(&self.user_dictionary)?;
+        load_stats.loading = start.elapsed();
 
         Ok(Romanizer {
-            tagger,
-            _tempdir: tempdir,
+            options: self.options,
+            load_stats,
+            overrides: self.overrides,
+            transliterator: self.transliterator,
+            tokenizer,
         })
     }
 
+    /// Build a [`Romanizer`] using `tokenizer` instead of loading an [`IgoTokenizer`]. The
+    /// dictionary settings including the user dictionary are ignored as no dictionary has to be
+    /// loaded; use [`IgoTokenizer::add_user_dictionary`] to add one to an [`IgoTokenizer`].
+    pub fn build_with_tokenizer<T: Tokenizer>(self, tokenizer: T) -> Romanizer<T> {
+        Romanizer {
+            options: self.options,
+            load_stats: LoadStats::default(),
+            overrides: self.overrides,
+            transliterator: self.transliterator,
+            tokenizer,
+        }
+    }
+}
+
+impl Romanizer {
+    /// Initialize a new [`Romanizer`] with the default [`RomanizeOptions`]. The first call takes
+    /// some time as the dictionary has to get extracted to the cache directory.
+    #[cfg(any(feature = "bundled-ipadic", feature = "bundled-ipadic-xz"))]
+    pub fn new() -> Result<Romanizer, RomanizeError> {
+        RomanizerBuilder::new().build()
+    }
+
+    /// Initialize a new [`Romanizer`] using the igo dictionary in `dictionary_dir`, e.g. a newer
+    /// IPADIC or one with additional words, instead of the bundled one.
+    ///
+    /// Fails with [`RomanizeError::MissingDictionaryFile`] or
+    /// [`RomanizeError::MalformedDictionaryFile`] if a file of the dictionary is missing or
+    /// obviously broken.
+    pub fn from_dictionary_dir<P: Into<PathBuf>>(
+        dictionary_dir: P,
+    ) -> Result<Romanizer, RomanizeError> {
+        RomanizerBuilder::new()
+            .dictionary_dir(dictionary_dir)
+            .build()
+    }
+
+    /// Create a [`RomanizerBuilder`] to customize the [`RomanizeOptions`].
+    pub fn builder() -> RomanizerBuilder {
+        RomanizerBuilder::new()
+    }
+}
+
+impl<T: Tokenizer> Romanizer<T> {
+    /// The tokenizer used to split the input into words.
+    pub fn tokenizer(&self) -> &T {
+        &self.tokenizer
+    }
+
+    /// How long loading the dictionary took, e.g. to check the cost of decompressing the bundled
+    /// dictionary.
+    pub fn load_stats(&self) -> &LoadStats {
+        &self.load_stats
+    }
+
+    /// The options used by [`Romanizer::romanize`] and [`Romanizer::analyze`].
+    pub fn options(&self) -> &RomanizeOptions {
+        &self.options
+    }
+
+    /// Change the options used by [`Romanizer::romanize`] and [`Romanizer::analyze`].
+    pub fn set_options(&mut self, options: RomanizeOptions) {
+        self.options = options;
+    }
+
     /// # Examples
     ///
-    /// ```
+    #[cfg_attr(
+        not(any(feature = "bundled-ipadic", feature = "bundled-ipadic-xz")),
+        doc = "```ignore"
+    )]
+    #[cfg_attr(
+        any(feature = "bundled-ipadic", feature = "bundled-ipadic-xz"),
+        doc = "```"
+    )]
     /// let romanizer = romanize::Romanizer::new().unwrap();
     /// assert_eq!(
     ///     romanizer.romanize("U&I ～夕日の綺麗なあの丘で～ U&I"),
     ///     "U&I ~Yūhi no Kirei na ano Oka de~ U&I",
     /// );
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the input can't be romanized. Use [`Romanizer::try_romanize`] to handle this case.
     pub fn romanize(&self, input: &str) -> String {
-        let mut romanized = hangeul::romanize(input);
+        self.try_romanize(input).unwrap()
+    }
 
-        let parts = self.tagger.parse(input);
-        let mut insert_space = false;
-        // Monotonically increasing index to the last replaced characters
-        let mut last_idx = 0;
-        for ref part in parts {
-            // Part features:
-            // 0 Part-of-speech
-            // 1 Part-of-speech subdivision class 1
-            // 2 Partspeech subdivision class 2
-            // 3 Partspeech subdivision class 3
-            // 4 Utilization type
-            // 5 Utilization form
-            // 6 Original form
-            // 7 Reading
-            // 8 Pronunciation
-
-            let feature = part.feature.split(',').collect::<Vec<_>>();
-
-            // Don't change punctuation; also don't update idx as other occurences will be found at
-            // places before the place of this part
-            if feature[0] == "記号" {
-                insert_space = false;
-                continue;
-            }
+    /// Like [`Romanizer::romanize`] but returns an error instead of panicking if the input can't
+    /// be romanized.
+    pub fn try_romanize(&self, input: &str) -> Result<String, RomanizeError> {
+        self.try_romanize_with(input, &self.options)
+    }
 
-            let idx = romanized.find(part.surface).unwrap();
+    /// Like [`Romanizer::romanize`] but with the given options instead of the ones the
+    /// [`Romanizer`] was built with.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the input can't be romanized. Use [`Romanizer::try_romanize_with`] to handle
+    /// this case.
+    pub fn romanize_with(&self, input: &str, options: &RomanizeOptions) -> String {
+        self.try_romanize_with(input, options).unwrap()
+    }
 
-            let katakana = feature.get(8).or(if is_katakana(part.surface) {
-                Some(&part.surface)
-            } else {
-                None
-            });
+    /// Like [`Romanizer::try_romanize`] but with the given options instead of the ones the
+    /// [`Romanizer`] was built with.
+    pub fn try_romanize_with(
+        &self,
+        input: &str,
+        options: &RomanizeOptions,
+    ) -> Result<String, RomanizeError> {
+        let tokens = self.analyze_with(input, options)?;
 
-            if let Some(katakana) = katakana {
-                let mut replacement = to_romaji(katakana);
+        let mut romanized = String::with_capacity(input.len() * 2);
+        let separator = options.separator.as_str();
+        // Whether the next word is preceded by the separator
+        let mut insert_space = false;
+        // Whether the next word starts a sentence
+        let mut sentence_start = true;
+        // Part-of-speech of the first token of the last word if later tokens may be attached to it
+        let mut head = None;
+        // Whether the last romanized token ends with a syllabic n
+        let mut syllabic_n = false;
+        // Byte offset into `input` up to which everything has been emitted
+        let mut cursor = 0;
+        for (i, token) in tokens.iter().enumerate() {
+            // Whitespace tagged as a symbol (e.g. U+3000) is handled like the skipped whitespace
+            if token.pos.is_symbol() && token.surface.chars().all(char::is_whitespace) {
+                continue;
+            }
+            // Emit whatever the tagger skipped (whitespace)
+            if token.range.start > cursor {
+                let skipped = &input[cursor..token.range.start];
+                let rest = &input[token.range.start..];
+                push_skipped(&mut romanized, skipped, rest, options.separator);
+                insert_space = false;
+                head = None;
+                syllabic_n = false;
+            }
+            cursor = token.range.end;
 
-                // Capitalize nouns
-                if feature[0] == "名詞" {
-                    replacement = uppercase_first_character(&replacement);
+            if token.overridden {
+                if insert_space {
+                    romanized.push_str(separator);
                 }
+                romanized.push_str(token.romaji.as_ref().map_or("", String::as_str));
+                insert_space = true;
+                sentence_start = false;
+                head = None;
+                syllabic_n = false;
+                continue;
+            }
 
-                replacement = replacement.replace('-', "\u{0304}");
+            // Don't change punctuation
+            if token.pos.is_symbol() {
+                romanized.push_str(&token.surface);
+                insert_space = false;
+                head = None;
+                syllabic_n = false;
+                if ends_sentence(token) {
+                    sentence_start = true;
+                }
+                continue;
+            }
 
-                if insert_space {
-                    replacement.insert(0, ' ');
+            if let Some(ref romaji) = token.romaji {
+                let builtin = self.transliterator.is_none();
+                // Never separate a doubled consonant from its sokuon (nakatta) or a sokuon token
+                // from the word it ends (cho')
+                let glued = i > 0
+                    && ((builtin && is_geminated(&tokens[i - 1], token))
+                        || is_lone_sokuon(&tokens[i - 1], token));
+                let attached = if glued {
+                    Some("")
+                } else {
+                    head.and_then(|head| joiner(head, token, options))
+                };
+                // Separate a syllabic n from a vowel or y of the next token if nothing else does:
+                // hon'ya. A custom transliterator decides about this on its own.
+                let joined = attached.unwrap_or(separator).is_empty();
+                if builtin && syllabic_n && joined && kana::starts_with_vowel_or_y(romaji) {
+                    romanized.push_str(options.syllabic_n_style().separator());
                 }
+                syllabic_n = ends_with_syllabic_n(token);
 
-                romanized = romanized.replacen(part.surface, &replacement, 1);
+                if let Some(joiner) = attached {
+                    romanized.push_str(joiner);
+                    romanized.push_str(romaji);
+                    // A prefix takes the part-of-speech of the word it's attached to
+                    if matches!(head, Some(PartOfSpeech::Prefix(_))) {
+                        head = Some(token.pos);
+                    }
+                } else {
+                    if insert_space {
+                        romanized.push_str(separator);
+                    }
+                    // Capitalize a prefix like the word it's attached to
+                    let next = tokens.get(i + 1).filter(|next| {
+                        next.range.start == token.range.end
+                            && next.romaji.is_some()
+                            && joiner(token.pos, next, options).is_some()
+                    });
+                    let word = capitalize(
+                        romaji,
+                        next.filter(|_| matches!(token.pos, PartOfSpeech::Prefix(_)))
+                            .unwrap_or(token),
+                        options.capitalization,
+                        sentence_start,
+                    );
+                    if options.separator == Separator::CamelCase {
+                        romanized.push_str(&uppercase_first_character(&word));
+                    } else {
+                        romanized.push_str(&word);
+                    }
+                    head = Some(token.pos);
+                }
                 insert_space = true;
+                sentence_start = false;
             } else {
-                // Only insert space if another word comes afterwards
-                if insert_space && part
-                    .surface
-                    .chars()
-                    .next()
-                    .map(|c| c.is_alphanumeric())
-                    .unwrap_or(false)
+                // Only insert the separator if another word comes afterwards
+                if insert_space
+                    && token
+                        .surface
+                        .chars()
+                        .next()
+                        .map(|c| c.is_alphanumeric())
+                        .unwrap_or(false)
                 {
-                    let idx = romanized[last_idx..].find(part.surface).unwrap();
-                    romanized.insert(idx + last_idx, ' ');
+                    romanized.push_str(separator);
                 }
+                // Words without a reading may still be Korean
+                romanized.push_str(&hangeul::romanize(&token.surface));
                 insert_space = false;
+                head = None;
+                syllabic_n = false;
+                if token.surface.chars().any(char::is_alphanumeric) {
+                    sentence_start = false;
+                }
+            }
+        }
+        push_skipped(&mut romanized, &input[cursor..], "", options.separator);
+
+        if options.normalize {
+            romanized = romanized.nfkc().collect();
+        }
+
+        Ok(romanized)
+    }
+
+    /// Split the input into [`Token`]s with their readings, part-of-speech and romanization.
+    ///
+    /// # Examples
+    ///
+    #[cfg_attr(
+        not(any(feature = "bundled-ipadic", feature = "bundled-ipadic-xz")),
+        doc = "```ignore"
+    )]
+    #[cfg_attr(
+        any(feature = "bundled-ipadic", feature = "bundled-ipadic-xz"),
+        doc = "```"
+    )]
+    /// let romanizer = romanize::Romanizer::new().unwrap();
+    /// let tokens = romanizer.analyze("東京の空").unwrap();
+    ///
+    /// assert_eq!(tokens[0].surface, "東京");
+    /// assert_eq!(tokens[0].range, 0..6);
+    /// assert_eq!(tokens[0].reading, Some("トウキョウ".to_string()));
+    /// assert_eq!(tokens[0].romaji, Some("tōkyō".to_string()));
+    /// ```
+    pub fn analyze(&self, input: &str) -> Result<Vec<Token>, RomanizeError> {
+        self.analyze_with(input, &self.options)
+    }
+
+    /// Like [`Romanizer::analyze`] but with the given options instead of the ones the
+    /// [`Romanizer`] was built with.
+    pub fn analyze_with(
+        &self,
+        input: &str,
+        options: &RomanizeOptions,
+    ) -> Result<Vec<Token>, RomanizeError> {
+        let mut tokens = Vec::new();
+        // Start of the text not covered by the overrides which still has to be tagged
+        let mut untagged = 0;
+        let mut offset = 0;
+        while offset < input.len() {
+            let rest = &input[offset..];
+            let (from, to) = match self.overrides.longest_match(rest) {
+                Some(entry) => entry,
+                None => {
+                    offset += rest.chars().next().map_or(1, char::len_utf8);
+                    continue;
+                }
+            };
+
+            self.tag(input, untagged..offset, &mut tokens)?;
+            let morpheme = Morpheme::new(from, PartOfSpeech::Other);
+            let mut token = Token::new(morpheme, offset..offset + from.len());
+            token.romaji = Some(to.to_string());
+            token.overridden = true;
+            offset = token.range.end;
+            untagged = offset;
+            tokens.push(token);
+        }
+        self.tag(input, untagged..input.len(), &mut tokens)?;
+
+        // Back to front so a sokuon at the end of a token can double the consonant of the next one
+        for i in (0..tokens.len()).rev() {
+            if tokens[i].overridden {
+                continue;
+            }
+            let next = tokens
+                .get(i + 1)
+                .filter(|next| next.range.start == tokens[i].range.end && !next.overridden)
+                .and_then(|next| next.romaji.as_ref());
+            let romaji = self.transliterate(&tokens[i], next.map(String::as_str), options);
+            tokens[i].romaji = romaji;
+        }
+
+        Ok(tokens)
+    }
+
+    /// Romanize the reading of `token`. `next` is the romanization of the token directly following
+    /// it, if any.
+    fn transliterate(
+        &self,
+        token: &Token,
+        next: Option<&str>,
+        options: &RomanizeOptions,
+    ) -> Option<String> {
+        if token.pos.is_symbol() {
+            return None;
+        }
+
+        // The pronunciation marks long vowels with ー (東京 is トーキョー) while the reading spells
+        // them out as written (トウキョウ). Particles are always taken from the pronunciation so は is
+        // still romanized as "wa".
+        let long_vowels = options.long_vowel_style();
+        let use_reading = !token.pos.is_particle()
+            && (long_vowels == LongVowelStyle::KanaFaithful
+                || options.reading_source == ReadingSource::Reading);
+        let kana = match (use_reading, token.reading.as_ref()) {
+            (true, Some(reading)) if long_vowels != LongVowelStyle::KanaFaithful => {
+                let pronunciation = token.pronunciation.as_deref();
+                Some(Cow::Owned(kana::mark_long_vowels(reading, pronunciation)))
+            }
+            (true, reading) => reading.map(|reading| Cow::Borrowed(&reading[..])),
+            (false, _) => token
+                .pronunciation
+                .as_ref()
+                .map(|pronunciation| Cow::Borrowed(&pronunciation[..])),
+        };
+        // Unknown words spelled in kana
+        let kana = kana.or(if is_kana(&token.surface) {
+            Some(Cow::Borrowed(&token.surface[..]))
+        } else {
+            None
+        });
+
+        kana.map(|kana| {
+            // A final sokuon doubles the consonant of the next token: ナカッ|タ is "nakatta". A
+            // custom transliterator gets the sokuon instead.
+            let geminate = next.filter(|next| {
+                self.transliterator.is_none()
+                    && (kana.ends_with('ッ') || kana.ends_with('っ'))
+                    && next.starts_with(|c: char| c.is_ascii_alphabetic())
+                    && !kana::starts_with_vowel_or_y(next)
+            });
+            let kana = match geminate {
+                Some(_) => &kana[..kana.len() - 'ッ'.len_utf8()],
+                None => &kana[..],
+            };
+
+            let mut romaji = match self.transliterator {
+                Some(ref transliterator) => transliterator.transliterate(kana, token, options),
+                None => options.system.transliterate(kana, token, options),
+            };
+            if let Some(next) = geminate {
+                kana::push_geminate(&mut romaji, &next.to_lowercase(), options.system);
             }
+            romaji
+        })
+    }
+
+    /// Tag `input[range]` with the tokenizer and append the tokens to `tokens`.
+    fn tag(
+        &self,
+        input: &str,
+        range: Range<usize>,
+        tokens: &mut Vec<Token>,
+    ) -> Result<(), RomanizeError> {
+        if range.start == range.end {
+            return Ok(());
+        }
+        let text = &input[range.clone()];
+        let mut cursor = 0;
+        for morpheme in self.tokenizer.tokenize(text)? {
+            let found = locate(text, morpheme.surface, cursor)
+                .ok_or_else(|| RomanizeError::SurfaceNotFound(morpheme.surface.to_string()))?;
+            cursor = found.end;
 
-            last_idx = idx;
+            let found = range.start + found.start..range.start + found.end;
+            tokens.push(Token::new(morpheme, found));
         }
 
-        romanized
-            .nfkc() // Normalize unicode
-            .to_string()
+        Ok(())
     }
 }
 
-// From https://stackoverflow.com/a/38406885
-fn uppercase_first_character(s: &str) -> String {
-    let mut c = s.chars();
-    match c.next() {
-        None => String::new(),
-        Some(f) => f.to_uppercase().to_string() + c.as_str(),
+/// Emit text the tagger skipped. Runs of whitespace are replaced with a single `separator`, which is
+/// left out at the start and end of the output and next to a separator in the text itself:
+/// 東京 - 大阪 becomes `Tōkyō-Ōsaka` rather than `Tōkyō---Ōsaka` with hyphens. `rest` is the input
+/// following `skipped`.
+fn push_skipped(romanized: &mut String, skipped: &str, rest: &str, separator: Separator) {
+    let separator = separator.as_str();
+    let mut skipped = skipped;
+    while !skipped.is_empty() {
+        let end = skipped.find(char::is_whitespace).unwrap_or(skipped.len());
+        romanized.push_str(&hangeul::romanize(&skipped[..end]));
+        skipped = &skipped[end..];
+
+        let end = skipped
+            .find(|c: char| !c.is_whitespace())
+            .unwrap_or(skipped.len());
+        skipped = &skipped[end..];
+        if end == 0 {
+            continue;
+        }
+        let following = if skipped.is_empty() { rest } else { skipped };
+        let before = romanized.chars().next_back();
+        let after = following.chars().next();
+        if let (Some(before), Some(after)) = (before, after) {
+            if !is_separator(before, separator) && !is_separator(after, separator) {
+                romanized.push_str(separator);
+            }
+        }
+    }
+}
+
+/// Whether `c` is the `separator`, also once normalized (e.g. the full-width hyphen －).
+fn is_separator(c: char, separator: &str) -> bool {
+    iter::once(c).nfkc().eq(separator.chars())
+}
+
+/// Romanize Korean text. Unlike [`Romanizer`] this doesn't need a dictionary.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(romanize::romanize_korean("서울"), "seoul");
+/// ```
+pub fn romanize_korean(input: &str) -> String {
+    hangeul::romanize(input)
+}
+
+#[cfg(any(feature = "bundled-ipadic", feature = "bundled-ipadic-xz"))]
+fn bundled_archive() -> Option<DictionaryArchive> {
+    Some(DictionaryArchive::bundled())
+}
+
+#[cfg(not(any(feature = "bundled-ipadic", feature = "bundled-ipadic-xz")))]
+fn bundled_archive() -> Option<DictionaryArchive> {
+    None
+}
+
+/// Find the byte range of `surface` in `input` at or after `cursor`.
+///
+/// The surfaces returned by the tokenizer are usually slices of the input so the range can be
+/// computed from the pointers; fall back to searching if that doesn't hold.
+fn locate(input: &str, surface: &str, cursor: usize) -> Option<Range<usize>> {
+    let start = (surface.as_ptr() as usize).wrapping_sub(input.as_ptr() as usize);
+    let end = start.wrapping_add(surface.len());
+    if start >= cursor && start <= end && input.get(start..end) == Some(surface) {
+        return Some(start..end);
     }
+
+    input[cursor..]
+        .find(surface)
+        .map(|idx| cursor + idx..cursor + idx + surface.len())
 }
 
-fn unzip(zip: &[u8], output_directory: &Path) -> Result<(), io::Error> {
-    // Use unwraps as we control the zip
-    let mut archive = ZipArchive::new(Cursor::new(zip)).unwrap();
+/// Apply `capitalization` to the romanized `token`.
+fn capitalize<'a>(
+    romaji: &'a str,
+    token: &Token,
+    capitalization: Capitalization,
+    sentence_start: bool,
+) -> Cow<'a, str> {
+    let is_proper_noun = matches!(token.pos, PartOfSpeech::Noun(NounKind::Proper(_)));
+    let capitalize = match capitalization {
+        Capitalization::Lowercase => false,
+        Capitalization::Nouns => token.pos.is_noun(),
+        Capitalization::ProperNouns => is_proper_noun,
+        Capitalization::Sentence => sentence_start || is_proper_noun,
+        Capitalization::Title => {
+            sentence_start || !(token.pos.is_particle() || token.pos == PartOfSpeech::AuxiliaryVerb)
+        }
+        Capitalization::AllCaps => return Cow::Owned(romaji.to_uppercase()),
+    };
+    if capitalize {
+        Cow::Owned(uppercase_first_character(romaji))
+    } else {
+        Cow::Borrowed(romaji)
+    }
+}
 
-    for i in 0..archive.len() {
-        let mut file = archive.by_index(i).unwrap();
-        let file_path = file.sanitized_name();
+/// How `token` is attached to the word starting with a token of part-of-speech `head`, if it is.
+fn joiner(head: PartOfSpeech, token: &Token, options: &RomanizeOptions) -> Option<&'static str> {
+    match (head, token.pos) {
+        (PartOfSpeech::Verb(_), _) | (PartOfSpeech::Adjective(_), _)
+            if options.word_division == WordDivision::Hepburn && is_inflection(token) =>
+        {
+            Some("")
+        }
+        (PartOfSpeech::Noun(_), PartOfSpeech::Noun(NounKind::Suffix(SuffixKind::Person)))
+            if options.hyphenate_honorifics =>
+        {
+            Some("-")
+        }
+        (PartOfSpeech::Noun(_), PartOfSpeech::Noun(NounKind::Suffix(kind)))
+            if options.hyphenate_compounds && kind != SuffixKind::Person =>
+        {
+            Some("-")
+        }
+        (PartOfSpeech::Prefix(_), PartOfSpeech::Noun(_)) if options.hyphenate_compounds => {
+            Some("-")
+        }
+        _ => None,
+    }
+}
 
-        // Only extract files (not directories) directly into output_directory
-        // (suffices for our use case)
-        if !(file.name()).ends_with('/') {
-            let mut outfile = File::create(&output_directory.join(file_path.file_name().unwrap()))?;
-            io::copy(&mut file, &mut outfile)?;
+/// The kana of `token`, or its surface if it has none.
+fn kana_of(token: &Token) -> &str {
+    token
+        .pronunciation
+        .as_ref()
+        .or(token.reading.as_ref())
+        .unwrap_or(&token.surface)
+}
+
+/// Whether the kana of `token` end with a syllabic n (ン).
+fn ends_with_syllabic_n(token: &Token) -> bool {
+    let kana = kana_of(token);
+    kana.ends_with('ン') || kana.ends_with('ん')
+}
+
+/// Whether the final sokuon of `token` doubled the consonant of `next` (ナカッ|タ is "nakatta"), see
+/// [`Romanizer::analyze_with`].
+fn is_geminated(token: &Token, next: &Token) -> bool {
+    let kana = kana_of(token);
+    token.range.end == next.range.start
+        && !token.overridden
+        && !next.overridden
+        && token.romaji.is_some()
+        && (kana.ends_with('ッ') || kana.ends_with('っ'))
+        && next
+            .romaji
+            .as_ref()
+            .map(|next| {
+                next.starts_with(|c: char| c.is_ascii_alphabetic())
+                    && !kana::starts_with_vowel_or_y(next)
+            })
+            .unwrap_or(false)
+}
+
+/// Whether `token` consists of a sokuon only and directly follows `prev` (ちょ|っ).
+fn is_lone_sokuon(prev: &Token, token: &Token) -> bool {
+    prev.range.end == token.range.start
+        && !prev.overridden
+        && !token.overridden
+        && prev.romaji.is_some()
+        && kana_of(token).chars().all(|c| c == 'ッ' || c == 'っ')
+}
+
+/// Whether `token` is attached to a preceding verb or adjective with Hepburn word division.
+fn is_inflection(token: &Token) -> bool {
+    match token.pos {
+        PartOfSpeech::AuxiliaryVerb
+        | PartOfSpeech::Verb(Dependency::Suffix)
+        | PartOfSpeech::Adjective(Dependency::Suffix) => true,
+        PartOfSpeech::Particle(ParticleKind::Conjunctive) => {
+            token.surface == "て" || token.surface == "で"
         }
+        _ => false,
     }
+}
 
-    Ok(())
+/// Whether `token` is punctuation ending a sentence.
+fn ends_sentence(token: &Token) -> bool {
+    token.pos == PartOfSpeech::Symbol(SymbolKind::Period)
+        || token
+            .surface
+            .chars()
+            .all(|c| matches!(c, '。' | '．' | '.' | '！' | '!' | '？' | '?'))
 }
 
-#[cfg(test)]
+// From https://stackoverflow.com/a/38406885
+fn uppercase_first_character(s: &str) -> String {
+    let mut c = s.chars();
+    match c.next() {
+        None => String::new(),
+        Some(f) => f.to_uppercase().to_string() + c.as_str(),
+    }
+}
+
+#[cfg(all(test, any(feature = "bundled-ipadic", feature = "bundled-ipadic-xz")))]
 mod tests {
     use super::*;
+    use tempfile::TempDir;
 
     #[test]
     fn romanize() {
         let romanizer = Romanizer::new().unwrap();
         assert_eq!(romanizer.romanize("太陽のKiss"), "Taiyō no Kiss");
-        assert_eq!(
-            romanizer.romanize("エブリデイワールド"),
-            "Eburideiwārudo"
-        );
+        assert_eq!(romanizer.romanize("エブリデイワールド"), "Eburideiwārudo");
         assert_eq!(
             romanizer.romanize("U&I ～夕日の綺麗なあの丘で～ U&I"),
             "U&I ~Yūhi no Kirei na ano Oka de~ U&I",
@@ -176,5 +915,462 @@ mod tests {
             romanizer.romanize("空の境界 「殺人考察（後）」Original Soundtrack"),
             "Sora no Kyōkai 「Satsujin Kōsatsu(Go)」Original Soundtrack",
         );
+        assert_eq!(
+            romanizer.romanize("空の空 ～空の境界～"),
+            "Sora no Sora ~Sora no Kyōkai~",
+        );
+    }
+
+    #[test]
+    fn cache_dir() {
+        let cache_dir = TempDir::new().unwrap();
+        for i in 0..2 {
+            let romanizer = Romanizer::builder()
+                .cache_dir(cache_dir.path())
+                .build()
+                .unwrap();
+            assert_eq!(romanizer.romanize("太陽"), "Taiyō");
+
+            // Only the first build has to extract the dictionary
+            let load_stats = romanizer.load_stats();
+            assert_eq!(load_stats.extraction.is_some(), i == 0);
+            assert_eq!(
+                load_stats.decompression.is_some(),
+                i == 0 && cfg!(feature = "bundled-ipadic-xz")
+            );
+        }
+        // Only the extracted dictionary remains
+        assert_eq!(::std::fs::read_dir(cache_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn in_memory() {
+        let cache_dir = TempDir::new().unwrap();
+        let romanizer = Romanizer::builder()
+            .cache_dir(cache_dir.path())
+            .disable_cache()
+            .build()
+            .unwrap();
+        assert_eq!(romanizer.romanize("太陽"), "Taiyō");
+        assert!(romanizer.load_stats().extraction.is_some());
+        assert_eq!(::std::fs::read_dir(cache_dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn dictionary_dir() {
+        let cache_dir = TempDir::new().unwrap();
+        let dictionary_dir = DictionaryArchive::bundled()
+            .extract_to_cache(cache_dir.path())
+            .unwrap();
+        let romanizer = Romanizer::from_dictionary_dir(&dictionary_dir).unwrap();
+        assert_eq!(romanizer.romanize("太陽"), "Taiyō");
+
+        ::std::fs::remove_file(dictionary_dir.join("word.inf")).unwrap();
+        match Romanizer::from_dictionary_dir(&dictionary_dir) {
+            Err(RomanizeError::MissingDictionaryFile(path)) => {
+                assert_eq!(path, dictionary_dir.join("word.inf"))
+            }
+            _ => panic!("expected missing word.inf"),
+        }
+
+        ::std::fs::write(dictionary_dir.join("code2category"), b"").unwrap();
+        match Romanizer::from_dictionary_dir(&dictionary_dir) {
+            Err(RomanizeError::MalformedDictionaryFile(path, _)) => {
+                assert_eq!(path, dictionary_dir.join("code2category"))
+            }
+            _ => panic!("expected malformed code2category"),
+        }
+    }
+
+    #[test]
+    fn user_dictionary() {
+        let user_dictionary = UserDictionary::parse(
+            "初音ミク,名詞-固有名詞-人名,ハツネミク\n東京,名詞-固有名詞-地域,ヒガシキョウ,ヒガシキョー\n\
+             ミク,名詞-固有名詞-人名,ミク\n",
+        )
+        .unwrap();
+        let romanizer = Romanizer::builder()
+            .user_dictionary(user_dictionary)
+            .build()
+            .unwrap();
+        assert_eq!(romanizer.romanize("初音ミクの空"), "Hatsunemiku no Sora");
+        assert_eq!(romanizer.romanize("太陽の東京"), "Taiyō no Higashikyō");
+
+        let tokens = romanizer.analyze("空 初音ミク").unwrap();
+        assert_eq!(tokens[1].surface, "初音ミク");
+        assert_eq!(tokens[1].range, 4..16);
+        assert_eq!(
+            tokens[1].pos,
+            PartOfSpeech::Noun(NounKind::Proper(ProperNounKind::Person))
+        );
+
+        // Entries don't split longer words
+        let tokens = romanizer.analyze("ミクロの空").unwrap();
+        assert_eq!(tokens[0].surface, "ミクロ");
+        assert_eq!(tokens[0].pos, PartOfSpeech::Noun(NounKind::Common));
+
+        // Instead of unknown words
+        let user_dictionary = UserDictionary::parse(
+            "きゃりーぱみゅぱみゅ,名詞-固有名詞-人名,キャリーパミュパミュ\n\
+             # Too expensive to win over 空 and の\n\
+             空の,,,20000,名詞,一般,*,*,*,*,空の,ソラノ\n",
+        )
+        .unwrap();
+        let romanizer = Romanizer::builder()
+            .user_dictionary(user_dictionary)
+            .build()
+            .unwrap();
+        assert_eq!(
+            romanizer.romanize("きゃりーぱみゅぱみゅの新曲"),
+            "Kyarīpamyupamyu no Shinkyoku"
+        );
+        assert_eq!(romanizer.romanize("空の空"), "Sora no Sora");
+        // Unknown words in hiragana are still romanized
+        assert_eq!(romanizer.romanize("ぱみゅぱみゅ"), "Pamyupamyu");
+
+        match Romanizer::builder()
+            .user_dictionary(
+                UserDictionary::parse("\n空,5000,5000,0,名詞,一般,*,*,*,*,空,ソラ").unwrap(),
+            )
+            .build()
+        {
+            Err(RomanizeError::InvalidUserDictionary(2, _)) => {}
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("expected out of range context ids"),
+        }
+    }
+
+    #[test]
+    fn overrides() {
+        let overrides = vec![
+            ("空の境界", "Kara no Kyōkai"),
+            ("ボールペン", "ballpoint pen"),
+        ]
+        .into_iter()
+        .collect::<Overrides>();
+        let romanizer = Romanizer::builder()
+            .overrides(overrides)
+            .user_dictionary(UserDictionary::parse("空の,名詞,ソラノ").unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(
+            romanizer.romanize("空の空 ～空の境界～ ボールペン"),
+            "Sorano Sora ~Kara no Kyōkai~ ballpoint pen",
+        );
+
+        let tokens = romanizer.analyze("空の境界").unwrap();
+        assert_eq!(tokens.len(), 1);
+        assert!(tokens[0].overridden);
+        assert_eq!(tokens[0].romaji, Some("Kara no Kyōkai".to_string()));
+    }
+
+    #[test]
+    fn reading_source() {
+        let mut romanizer = Romanizer::new().unwrap();
+        assert_eq!(romanizer.romanize("大阪は東京へ"), "Ōsaka wa Tōkyō e");
+        assert_eq!(romanizer.romanize("通りを"), "Tōri o");
+        assert_eq!(romanizer.romanize("大きい空"), "ōkii Sora");
+
+        romanizer.set_options(RomanizeOptions {
+            reading_source: ReadingSource::Reading,
+            ..RomanizeOptions::default()
+        });
+        assert_eq!(romanizer.romanize("大阪は東京へ"), "Oosaka wa Tōkyō e");
+        assert_eq!(romanizer.romanize("通りを"), "Toori o");
+        assert_eq!(romanizer.romanize("大きい空を思う"), "ookii Sora o omou");
+    }
+
+    #[test]
+    fn capitalization() {
+        let romanizer = Romanizer::new().unwrap();
+        let romanize = |input, capitalization| {
+            let options = RomanizeOptions {
+                capitalization,
+                ..RomanizeOptions::default()
+            };
+            romanizer.romanize_with(input, &options)
+        };
+
+        let input = "ふでペン 東京の空。空の境界";
+        assert_eq!(
+            romanize(input, Capitalization::Lowercase),
+            "fu de pen tōkyō no sora。sora no kyōkai",
+        );
+        assert_eq!(
+            romanize(input, Capitalization::Nouns),
+            "fu de Pen Tōkyō no Sora。Sora no Kyōkai",
+        );
+        assert_eq!(
+            romanize(input, Capitalization::ProperNouns),
+            "fu de pen Tōkyō no sora。sora no kyōkai",
+        );
+        assert_eq!(
+            romanize(input, Capitalization::Sentence),
+            "Fu de pen Tōkyō no sora。Sora no kyōkai",
+        );
+        assert_eq!(
+            romanize(input, Capitalization::Title),
+            "Fu de Pen Tōkyō no Sora。Sora no Kyōkai",
+        );
+        assert_eq!(
+            romanize(input, Capitalization::AllCaps),
+            "FU DE PEN TŌKYŌ NO SORA。SORA NO KYŌKAI",
+        );
+        assert_eq!(
+            romanize("「綺麗なあの丘」", Capitalization::Title),
+            "「Kirei na Ano Oka」",
+        );
+    }
+
+    #[test]
+    fn word_division() {
+        let mut romanizer = Romanizer::new().unwrap();
+        assert_eq!(romanizer.romanize("食べています"), "tabe te i masu");
+        assert_eq!(romanizer.romanize("食べられない"), "tabe rare nai");
+
+        romanizer.set_options(RomanizeOptions {
+            word_division: WordDivision::Hepburn,
+            ..RomanizeOptions::default()
+        });
+        assert_eq!(romanizer.romanize("食べています"), "tabete imasu");
+        assert_eq!(romanizer.romanize("食べられない"), "taberarenai");
+        // Only verbs and adjectives take inflections
+        assert_eq!(romanizer.romanize("綺麗なあの丘で"), "Kirei na ano Oka de");
+    }
+
+    #[test]
+    fn hyphenation() {
+        let romanizer = Romanizer::new().unwrap();
+        let romanize = |input, hyphenate_honorifics, hyphenate_compounds| {
+            let options = RomanizeOptions {
+                hyphenate_honorifics,
+                hyphenate_compounds,
+                ..RomanizeOptions::default()
+            };
+            romanizer.romanize_with(input, &options)
+        };
+
+        let input = "田中さんと東京都でお酒";
+        assert_eq!(
+            romanize(input, false, false),
+            "Tanaka San to Tōkyō To de o Sake"
+        );
+        assert_eq!(
+            romanize(input, true, false),
+            "Tanaka-san to Tōkyō To de o Sake"
+        );
+        assert_eq!(
+            romanize(input, true, true),
+            "Tanaka-san to Tōkyō-to de O-sake"
+        );
+    }
+
+    #[test]
+    fn separator() {
+        let romanizer = Romanizer::new().unwrap();
+        let romanize = |separator| {
+            let options = RomanizeOptions {
+                separator,
+                ..RomanizeOptions::default()
+            };
+            romanizer.romanize_with("空の境界 Kiss", &options)
+        };
+
+        assert_eq!(romanize(Separator::Space), "Sora no Kyōkai Kiss");
+        assert_eq!(romanize(Separator::Hyphen), "Sora-no-Kyōkai-Kiss");
+        assert_eq!(romanize(Separator::Underscore), "Sora_no_Kyōkai_Kiss");
+        assert_eq!(romanize(Separator::CamelCase), "SoraNoKyōkaiKiss");
+        assert_eq!(romanize(Separator::None), "SoranoKyōkaiKiss");
+
+        // Runs of whitespace become a single separator
+        let romanize = |input, separator| {
+            let options = RomanizeOptions {
+                separator,
+                ..RomanizeOptions::default()
+            };
+            romanizer.romanize_with(input, &options)
+        };
+        assert_eq!(
+            romanize("空  \u{3000}Kiss の空", Separator::Underscore),
+            "Sora_Kiss_no_Sora"
+        );
+        assert_eq!(
+            romanize(" 空\t\tKiss  の空 ", Separator::Space),
+            "Sora Kiss no Sora"
+        );
+        // Unless the text has one already
+        assert_eq!(romanize("東京 - 大阪", Separator::Hyphen), "Tōkyō-Ōsaka");
+        assert_eq!(romanize("東京 － 大阪", Separator::Hyphen), "Tōkyō-Ōsaka");
+        assert_eq!(romanize("東京 - 大阪", Separator::Space), "Tōkyō - Ōsaka");
+    }
+
+    #[test]
+    fn syllabic_n() {
+        let romanizer = Romanizer::new().unwrap();
+        let romanize = |input, syllabic_n, separator, hyphenate_compounds| {
+            let options = RomanizeOptions {
+                syllabic_n,
+                separator,
+                hyphenate_compounds,
+                ..RomanizeOptions::default()
+            };
+            romanizer.romanize_with(input, &options)
+        };
+
+        assert_eq!(romanize("単位", None, Separator::Space, false), "Tan'i");
+        assert_eq!(
+            romanize(
+                "単位",
+                Some(SyllabicNStyle::Hyphen),
+                Separator::Space,
+                false
+            ),
+            "Tan-i"
+        );
+        assert_eq!(romanize("本屋", None, Separator::Space, false), "Hon'ya");
+        // Across tokens (千|円) only if they aren't separated otherwise
+        assert_eq!(romanize("千円", None, Separator::Space, false), "Sen En");
+        assert_eq!(romanize("千円", None, Separator::Space, true), "Sen-en");
+        assert_eq!(
+            romanize(
+                "千円",
+                Some(SyllabicNStyle::Apostrophe),
+                Separator::None,
+                false
+            ),
+            "Sen'En"
+        );
+        // Omitted by default without separators
+        assert_eq!(romanize("千円", None, Separator::None, false), "SenEn");
+        assert_eq!(romanize("原因", None, Separator::CamelCase, false), "Genin");
+        assert_eq!(romanize("原因", None, Separator::Hyphen, false), "Gen'in");
+
+        // Not applied to the output of a custom transliterator
+        let romanizer = Romanizer::builder()
+            .transliterator(RomanizationSystem::ModifiedHepburn)
+            .separator(Separator::None)
+            .build()
+            .unwrap();
+        assert_eq!(romanizer.romanize("千円"), "SenEn");
+    }
+
+    #[test]
+    fn sokuon() {
+        let romanizer = Romanizer::new().unwrap();
+        let romanize = |input, word_division, sokuon| {
+            let options = RomanizeOptions {
+                word_division,
+                sokuon,
+                ..RomanizeOptions::default()
+            };
+            romanizer.romanize_with(input, &options)
+        };
+
+        assert_eq!(
+            romanize(
+                "食べなかった",
+                WordDivision::Hepburn,
+                SokuonStyle::default()
+            ),
+            "tabenakatta"
+        );
+        assert_eq!(
+            romanize("食べなかった", WordDivision::Tokens, SokuonStyle::default()),
+            "tabe nakatta"
+        );
+        assert_eq!(
+            romanize("あっ!", WordDivision::Tokens, SokuonStyle::Apostrophe),
+            "a'!"
+        );
+        assert_eq!(
+            romanize("あっ!", WordDivision::Tokens, SokuonStyle::Tsu),
+            "a-tsu!"
+        );
+        assert_eq!(
+            romanize("あっ!", WordDivision::Tokens, SokuonStyle::Omitted),
+            "a!"
+        );
+        // A sokuon tagged on its own stays with the word before it
+        assert_eq!(
+            romanize("ちょっ", WordDivision::Tokens, SokuonStyle::Apostrophe),
+            "Cho'"
+        );
+    }
+
+    #[test]
+    fn transliterator() {
+        struct Particles;
+        impl Transliterator for Particles {
+            fn transliterate(
+                &self,
+                kana: &str,
+                token: &Token,
+                options: &RomanizeOptions,
+            ) -> String {
+                if token.pos.is_particle() {
+                    format!("<{}>", kana)
+                } else {
+                    RomanizationSystem::KunreiShiki.transliterate(kana, token, options)
+                }
+            }
+        }
+
+        let romanizer = Romanizer::builder()
+            .transliterator(Particles)
+            .build()
+            .unwrap();
+        assert_eq!(romanizer.romanize("太陽のKiss"), "Taiyô <ノ> Kiss");
+
+        // The sokuon is left to the transliterator instead of doubling the next consonant
+        struct Kana;
+        impl Transliterator for Kana {
+            fn transliterate(&self, kana: &str, _: &Token, _: &RomanizeOptions) -> String {
+                format!("[{}]", kana)
+            }
+        }
+
+        let romanizer = Romanizer::builder().transliterator(Kana).build().unwrap();
+        assert_eq!(romanizer.romanize("食べなかった"), "[タベ] [ナカッ] [タ]");
+    }
+
+    #[test]
+    fn romanize_with() {
+        let romanizer = Romanizer::new().unwrap();
+        let romanize = |input, options| romanizer.romanize_with(input, &options);
+
+        let kunrei = RomanizeOptions {
+            system: RomanizationSystem::KunreiShiki,
+            ..RomanizeOptions::default()
+        };
+        assert_eq!(romanize("太陽のKiss", kunrei), "Taiyô no Kiss");
+        let passport = RomanizeOptions {
+            system: RomanizationSystem::PassportHepburn,
+            ..RomanizeOptions::default()
+        };
+        assert_eq!(romanize("太陽のKiss", passport), "Taiyo no Kiss");
+
+        let faithful = RomanizeOptions {
+            long_vowels: Some(LongVowelStyle::KanaFaithful),
+            ..RomanizeOptions::default()
+        };
+        assert_eq!(romanize("東京", faithful), "Toukyou");
+        let omitted = RomanizeOptions {
+            long_vowels: Some(LongVowelStyle::Omitted),
+            ..RomanizeOptions::default()
+        };
+        assert_eq!(romanize("東京", omitted), "Tokyo");
+        let circumflex = RomanizeOptions {
+            long_vowels: Some(LongVowelStyle::Circumflex),
+            ..RomanizeOptions::default()
+        };
+        assert_eq!(romanize("ボールペン", circumflex), "Bôrupen");
+
+        let plain = RomanizeOptions {
+            capitalization: Capitalization::Lowercase,
+            separator: Separator::None,
+            normalize: false,
+            ..RomanizeOptions::default()
+        };
+        assert_eq!(romanize("空の境界（後）", plain), "soranokyōkai（go）");
     }
 }
@@ -0,0 +1,171 @@
+use kana::{LongVowelStyle, RomanizationSystem, SokuonStyle, SyllabicNStyle};
+
+/// Options controlling how the input is romanized.
+///
+/// These are set once for a [`Romanizer`](::Romanizer) through the
+/// [`RomanizerBuilder`](::RomanizerBuilder) but can also be passed per call, e.g. to
+/// [`Romanizer::romanize_with`](::Romanizer::romanize_with).
+///
+/// # Examples
+///
+/// ```
+/// use romanize::{Capitalization, RomanizationSystem, RomanizeOptions};
+///
+/// let options = RomanizeOptions {
+///     system: RomanizationSystem::KunreiShiki,
+///     capitalization: Capitalization::Lowercase,
+///     ..RomanizeOptions::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RomanizeOptions {
+    /// The romanization system. Defaults to [`RomanizationSystem::ModifiedHepburn`].
+    pub system: RomanizationSystem,
+    /// How long vowels are written. `None` (the default) uses the style conventionally used with
+    /// `system`.
+    pub long_vowels: Option<LongVowelStyle>,
+    /// How a syllabic n is separated from a following vowel or y, also across words which aren't
+    /// separated by [`RomanizeOptions::separator`] unless a custom
+    /// [`Transliterator`](::Transliterator) is used. `None` (the default) uses the style
+    /// conventionally used with `system`, or [`SyllabicNStyle::Omitted`] with
+    /// [`Separator::CamelCase`] and [`Separator::None`] to keep apostrophes out of identifiers:
+    /// 原因 is `Genin` then.
+    pub syllabic_n: Option<SyllabicNStyle>,
+    /// How a sokuon is written at the end of a word or before text which isn't romanized, e.g. in
+    /// あっ!. Defaults to [`SokuonStyle::Apostrophe`].
+    pub sokuon: SokuonStyle,
+    /// Which kana of a word are romanized. Defaults to [`ReadingSource::Pronunciation`].
+    pub reading_source: ReadingSource,
+    /// Which romanized words are capitalized. Defaults to [`Capitalization::Nouns`].
+    pub capitalization: Capitalization,
+    /// How the tokens are grouped into words. Defaults to [`WordDivision::Tokens`].
+    pub word_division: WordDivision,
+    /// Attach honorific suffixes (名詞,接尾,人名) like さん or 様 to the name with a hyphen:
+    /// `Tanaka-san`. Defaults to `false`.
+    pub hyphenate_honorifics: bool,
+    /// Attach other noun suffixes (名詞,接尾) and prefixes (接頭詞) to their noun with a hyphen:
+    /// `Tōkyō-to`, `o-sake`. Defaults to `false`.
+    pub hyphenate_compounds: bool,
+    /// What is put between romanized words. Defaults to [`Separator::Space`].
+    pub separator: Separator,
+    /// Apply NFKC normalization to the output which e.g. turns full-width characters into their
+    /// ASCII counterparts. Defaults to `true`.
+    pub normalize: bool,
+}
+
+impl Default for RomanizeOptions {
+    fn default() -> RomanizeOptions {
+        RomanizeOptions {
+            system: RomanizationSystem::default(),
+            long_vowels: None,
+            syllabic_n: None,
+            sokuon: SokuonStyle::default(),
+            reading_source: ReadingSource::default(),
+            capitalization: Capitalization::default(),
+            word_division: WordDivision::default(),
+            hyphenate_honorifics: false,
+            hyphenate_compounds: false,
+            separator: Separator::default(),
+            normalize: true,
+        }
+    }
+}
+
+/// Which romanized words start with a capital letter. Text which isn't romanized, e.g. English
+/// words, is kept as is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Capitalization {
+    /// Keep all romanized words lowercase: `taiyō no kiss`.
+    Lowercase,
+    /// Capitalize nouns: `Taiyō no Kiss`, `fu de Pen`.
+    #[default]
+    Nouns,
+    /// Capitalize proper nouns (固有名詞) only: `Tōkyō no sora`.
+    ProperNouns,
+    /// Capitalize the first word of each sentence and proper nouns: `Sora no Tōkyō`.
+    Sentence,
+    /// Capitalize the first word and all others except particles and auxiliary verbs:
+    /// `Fu de Pen`, `Kirei na Ano Oka de`.
+    Title,
+    /// Write romanized words in capital letters: `TAIYŌ NO Kiss`.
+    AllCaps,
+}
+
+/// How tokens of the tagger are grouped into romanized words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum WordDivision {
+    /// Every token is a word of its own: `tabe te i masu`.
+    #[default]
+    Tokens,
+    /// Hepburn word division: auxiliary verbs (助動詞), verb suffixes like られる and the
+    /// connective て are attached to the verb or adjective they follow while particles and
+    /// subsidiary verbs like いる stay separate: `tabete imasu`.
+    Hepburn,
+}
+
+/// What is put between two romanized words. Runs of whitespace in the input are replaced with a
+/// single one as well (and dropped for [`Separator::CamelCase`] and [`Separator::None`]) unless
+/// they're next to the separator already: 東京 - 大阪 is `Tōkyō-Ōsaka` with [`Separator::Hyphen`].
+/// Whitespace at the start and end of the input is dropped and punctuation is kept as is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Separator {
+    /// A space: `Sora no Kyōkai`.
+    #[default]
+    Space,
+    /// A hyphen: `Sora-no-Kyōkai`.
+    Hyphen,
+    /// An underscore: `Sora_no_Kyōkai`.
+    Underscore,
+    /// Nothing, but every word starts with a capital letter: `SoraNoKyōkai`.
+    CamelCase,
+    /// Nothing: `SoranoKyōkai`.
+    None,
+}
+
+impl Separator {
+    /// The string put between two words.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Separator::Space => " ",
+            Separator::Hyphen => "-",
+            Separator::Underscore => "_",
+            Separator::CamelCase | Separator::None => "",
+        }
+    }
+}
+
+/// Whether words are romanized from their reading or their pronunciation.
+///
+/// Particles always use the pronunciation so は, へ and を become `wa`, `e` and `o`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ReadingSource {
+    /// The pronunciation, e.g. オーサカ for 大阪 (`Ōsaka`).
+    #[default]
+    Pronunciation,
+    /// The reading as written, e.g. オオサカ for 大阪 (`Oosaka`) and オオキイ for 大きい (`ookii`).
+    /// Only a ウ lengthening an o or u is treated as long vowel, and only where the pronunciation
+    /// confirms it: トウキョウ is `Tōkyō` but 湖 (ミズウミ) stays `mizuumi`.
+    Reading,
+}
+
+impl RomanizeOptions {
+    /// The [`LongVowelStyle`] in effect, taking the default of the system into account.
+    pub fn long_vowel_style(&self) -> LongVowelStyle {
+        self.long_vowels
+            .unwrap_or_else(|| self.system.long_vowel_style())
+    }
+
+    /// The [`SyllabicNStyle`] in effect, taking the defaults of the system and the separator into
+    /// account.
+    pub fn syllabic_n_style(&self) -> SyllabicNStyle {
+        self.syllabic_n_style_for(self.system)
+    }
+
+    /// Like [`RomanizeOptions::syllabic_n_style`] but with the default of `system`.
+    pub(crate) fn syllabic_n_style_for(&self, system: RomanizationSystem) -> SyllabicNStyle {
+        self.syllabic_n.unwrap_or_else(|| match self.separator {
+            Separator::CamelCase | Separator::None => SyllabicNStyle::Omitted,
+            _ => system.syllabic_n_style(),
+        })
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+#[cfg(any(feature = "overrides-toml", feature = "overrides-json"))]
+use error::RomanizeError;
+use user_dictionary;
+
+/// Fixed romanizations for exact strings, e.g. official romanizations of artist names.
+///
+/// Matching parts of the input are never passed to the tagger but replaced by their romanization
+/// as is, without capitalization. If entries overlap, the longest one starting first wins. They
+/// also take precedence over a [`UserDictionary`](::UserDictionary).
+///
+/// # Examples
+///
+#[cfg_attr(
+    not(any(feature = "bundled-ipadic", feature = "bundled-ipadic-xz")),
+    doc = "```ignore"
+)]
+#[cfg_attr(
+    any(feature = "bundled-ipadic", feature = "bundled-ipadic-xz"),
+    doc = "```"
+)]
+/// use romanize::{Overrides, Romanizer};
+///
+/// let mut overrides = Overrides::new();
+/// overrides.insert("殺人考察（後）", "Satsujin Kōsatsu (Kō)");
+/// let romanizer = Romanizer::builder().overrides(overrides).build().unwrap();
+/// assert_eq!(
+///     romanizer.romanize("空の境界 「殺人考察（後）」"),
+///     "Sora no Kyōkai 「Satsujin Kōsatsu (Kō)」",
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Overrides {
+    entries: HashMap<String, String>,
+    // Length of the longest key in bytes
+    max_len: usize,
+}
+
+impl Overrides {
+    /// An empty table.
+    pub fn new() -> Overrides {
+        Overrides::default()
+    }
+
+    /// Parse a TOML table mapping each string to its romanization:
+    ///
+    /// ```toml
+    /// "殺人考察（後）" = "Satsujin Kōsatsu (Kō)"
+    /// "初音ミク" = "Hatsune Miku"
+    /// ```
+    ///
+    /// Requires the `overrides-toml` feature.
+    #[cfg(feature = "overrides-toml")]
+    pub fn from_toml(input: &str) -> Result<Overrides, RomanizeError> {
+        use toml::Value;
+
+        let table = match input.parse::<Value>() {
+            Ok(Value::Table(table)) => table,
+            Ok(_) => return Err(RomanizeError::InvalidOverrides("expected a table".into())),
+            Err(e) => return Err(RomanizeError::InvalidOverrides(e.to_string())),
+        };
+        table
+            .into_iter()
+            .map(|(from, to)| match to {
+                Value::String(to) => Ok((from, to)),
+                _ => Err(invalid_value(&from)),
+            })
+            .collect()
+    }
+
+    /// Parse a JSON object mapping each string to its romanization:
+    ///
+    /// ```json
+    /// {
+    ///     "殺人考察（後）": "Satsujin Kōsatsu (Kō)",
+    ///     "初音ミク": "Hatsune Miku"
+    /// }
+    /// ```
+    ///
+    /// Requires the `overrides-json` feature.
+    #[cfg(feature = "overrides-json")]
+    pub fn from_json(input: &str) -> Result<Overrides, RomanizeError> {
+        use serde_json::Value;
+
+        let object = match serde_json::from_str::<Value>(input) {
+            Ok(Value::Object(object)) => object,
+            Ok(_) => return Err(RomanizeError::InvalidOverrides("expected an object".into())),
+            Err(e) => return Err(RomanizeError::InvalidOverrides(e.to_string())),
+        };
+        object
+            .into_iter()
+            .map(|(from, to)| match to {
+                Value::String(to) => Ok((from, to)),
+                _ => Err(invalid_value(&from)),
+            })
+            .collect()
+    }
+
+    /// Romanize `from` as `to`, replacing an existing entry for `from`. Empty strings are
+    /// ignored.
+    pub fn insert<S: Into<String>, T: Into<String>>(&mut self, from: S, to: T) {
+        let from = from.into();
+        if from.is_empty() {
+            return;
+        }
+        self.max_len = self.max_len.max(from.len());
+        self.entries.insert(from, to.into());
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The longest entry `input` starts with and its romanization.
+    pub(crate) fn longest_match<'a>(&'a self, input: &str) -> Option<(&'a str, &'a str)> {
+        user_dictionary::longest_match(&self.entries, self.max_len, input)
+            .map(|(from, to)| (&from[..], &to[..]))
+    }
+}
+
+impl<S: Into<String>, T: Into<String>> Extend<(S, T)> for Overrides {
+    fn extend<I: IntoIterator<Item = (S, T)>>(&mut self, iter: I) {
+        for (from, to) in iter {
+            self.insert(from, to);
+        }
+    }
+}
+
+impl<S: Into<String>, T: Into<String>> FromIterator<(S, T)> for Overrides {
+    fn from_iter<I: IntoIterator<Item = (S, T)>>(iter: I) -> Overrides {
+        let mut overrides = Overrides::new();
+        overrides.extend(iter);
+        overrides
+    }
+}
+
+#[cfg(any(feature = "overrides-toml", feature = "overrides-json"))]
+fn invalid_value(key: &str) -> RomanizeError {
+    RomanizeError::InvalidOverrides(format!("the value of \"{}\" isn't a string", key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_match() {
+        let overrides = vec![("空の境界", "Kara no Kyōkai"), ("空", "Sora"), ("", "")]
+            .into_iter()
+            .collect::<Overrides>();
+        assert_eq!(overrides.len(), 2);
+        assert_eq!(
+            overrides.longest_match("空の境界 ～"),
+            Some(("空の境界", "Kara no Kyōkai"))
+        );
+        assert_eq!(overrides.longest_match("空の"), Some(("空", "Sora")));
+        assert_eq!(overrides.longest_match("の空"), None);
+    }
+
+    #[cfg(feature = "overrides-toml")]
+    #[test]
+    fn from_toml() {
+        let overrides = Overrides::from_toml("\"初音ミク\" = \"Hatsune Miku\"").unwrap();
+        assert_eq!(
+            overrides.longest_match("初音ミク"),
+            Some(("初音ミク", "Hatsune Miku"))
+        );
+        match Overrides::from_toml("\"初音ミク\" = 39") {
+            Err(RomanizeError::InvalidOverrides(_)) => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[cfg(feature = "overrides-json")]
+    #[test]
+    fn from_json() {
+        let overrides = Overrides::from_json("{\"初音ミク\": \"Hatsune Miku\"}").unwrap();
+        assert_eq!(
+            overrides.longest_match("初音ミク"),
+            Some(("初音ミク", "Hatsune Miku"))
+        );
+        match Overrides::from_json("[\"初音ミク\"]") {
+            Err(RomanizeError::InvalidOverrides(_)) => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+}
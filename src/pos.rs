@@ -0,0 +1,473 @@
+/// Part-of-speech of a token following the IPADIC classification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PartOfSpeech {
+    /// 名詞
+    Noun(NounKind),
+    /// 動詞
+    Verb(Dependency),
+    /// 形容詞
+    Adjective(Dependency),
+    /// 副詞
+    Adverb,
+    /// 連体詞
+    Adnominal,
+    /// 接続詞
+    Conjunction,
+    /// 助詞
+    Particle(ParticleKind),
+    /// 助動詞
+    AuxiliaryVerb,
+    /// 感動詞
+    Interjection,
+    /// 接頭詞
+    Prefix(PrefixKind),
+    /// 記号
+    Symbol(SymbolKind),
+    /// フィラー
+    Filler,
+    /// その他 and anything not covered by IPADIC
+    Other,
+}
+
+/// Subdivision of verbs and adjectives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dependency {
+    /// 自立
+    Independent,
+    /// 非自立, e.g. いる in 食べている
+    Dependent,
+    /// 接尾
+    Suffix,
+    /// Unknown subdivision
+    Other,
+}
+
+/// Subdivision of nouns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NounKind {
+    /// 一般
+    Common,
+    /// 固有名詞
+    Proper(ProperNounKind),
+    /// 代名詞
+    Pronoun,
+    /// サ変接続, nouns which can be used with する
+    VerbalNoun,
+    /// 形容動詞語幹, nouns which can be used with な
+    AdjectivalNoun,
+    /// 数
+    Numeral,
+    /// 副詞可能
+    Adverbial,
+    /// 非自立
+    Dependent,
+    /// 接尾
+    Suffix(SuffixKind),
+    /// 特殊
+    Special,
+    /// ナイ形容詞語幹
+    NaiAdjectiveStem,
+    /// 動詞非自立的
+    VerbLike,
+    /// 引用文字列
+    Quotation,
+    /// 接続詞的
+    Conjunctive,
+    /// Unknown subdivision
+    Other,
+}
+
+/// Subdivision of proper nouns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProperNounKind {
+    /// 一般
+    General,
+    /// 人名 (一般)
+    Person,
+    /// 人名 (姓)
+    Surname,
+    /// 人名 (名)
+    GivenName,
+    /// 組織
+    Organization,
+    /// 地域 (一般)
+    Place,
+    /// 地域 (国)
+    Country,
+    /// Unknown subdivision
+    Other,
+}
+
+/// Subdivision of noun suffixes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SuffixKind {
+    /// 一般
+    General,
+    /// 人名, honorifics like さん or 様
+    Person,
+    /// 地域, e.g. 都 or 市
+    Place,
+    /// 助数詞, counters
+    Counter,
+    /// サ変接続
+    VerbalNoun,
+    /// 形容動詞語幹
+    AdjectivalNoun,
+    /// 副詞可能
+    Adverbial,
+    /// 助動詞語幹
+    AuxiliaryVerbStem,
+    /// 特殊
+    Special,
+    /// Unknown subdivision
+    Other,
+}
+
+/// Subdivision of particles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParticleKind {
+    /// 格助詞, e.g. が, を or に
+    Case,
+    /// 係助詞, e.g. は or も
+    Binding,
+    /// 副助詞, e.g. まで or ばかり
+    Adverbial,
+    /// 接続助詞, e.g. て or から
+    Conjunctive,
+    /// 終助詞, e.g. か or よ
+    SentenceEnding,
+    /// 並立助詞, e.g. と or や
+    Coordinating,
+    /// 連体化, の
+    Adnominal,
+    /// 副助詞／並立助詞／終助詞, か
+    AdverbialCoordinatingSentenceEnding,
+    /// 副詞化, に and と
+    Adverbializer,
+    /// 特殊
+    Special,
+    /// Unknown subdivision
+    Other,
+}
+
+/// Subdivision of prefixes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrefixKind {
+    /// 名詞接続
+    Noun,
+    /// 動詞接続
+    Verb,
+    /// 形容詞接続
+    Adjective,
+    /// 数接続
+    Numeral,
+    /// Unknown subdivision
+    Other,
+}
+
+/// Subdivision of symbols.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    /// 一般
+    General,
+    /// 句点
+    Period,
+    /// 読点
+    Comma,
+    /// 空白
+    Space,
+    /// 括弧開
+    OpeningBracket,
+    /// 括弧閉
+    ClosingBracket,
+    /// アルファベット
+    Alphabet,
+    /// Unknown subdivision
+    Other,
+}
+
+impl PartOfSpeech {
+    /// Parse the part-of-speech from the first four features of the tagger (the part-of-speech
+    /// and its three subdivision classes). Missing features are treated like `*`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use romanize::{NounKind, PartOfSpeech, ProperNounKind};
+    ///
+    /// assert_eq!(
+    ///     PartOfSpeech::from_features(&["名詞", "固有名詞", "地域", "一般"]),
+    ///     PartOfSpeech::Noun(NounKind::Proper(ProperNounKind::Place)),
+    /// );
+    /// ```
+    pub fn from_features<S: AsRef<str>>(features: &[S]) -> PartOfSpeech {
+        let feature = |i: usize| features.get(i).map(|f| f.as_ref()).unwrap_or("*");
+        let (pos, sub1, sub2, sub3) = (feature(0), feature(1), feature(2), feature(3));
+
+        match pos {
+            "名詞" => PartOfSpeech::Noun(match sub1 {
+                "一般" => NounKind::Common,
+                "固有名詞" => NounKind::Proper(match (sub2, sub3) {
+                    ("一般", _) => ProperNounKind::General,
+                    ("人名", "姓") => ProperNounKind::Surname,
+                    ("人名", "名") => ProperNounKind::GivenName,
+                    ("人名", _) => ProperNounKind::Person,
+                    ("組織", _) => ProperNounKind::Organization,
+                    ("地域", "国") => ProperNounKind::Country,
+                    ("地域", _) => ProperNounKind::Place,
+                    _ => ProperNounKind::Other,
+                }),
+                "代名詞" => NounKind::Pronoun,
+                "サ変接続" => NounKind::VerbalNoun,
+                "形容動詞語幹" => NounKind::AdjectivalNoun,
+                "数" => NounKind::Numeral,
+                "副詞可能" => NounKind::Adverbial,
+                "非自立" => NounKind::Dependent,
+                "接尾" => NounKind::Suffix(match sub2 {
+                    "一般" => SuffixKind::General,
+                    "人名" => SuffixKind::Person,
+                    "地域" => SuffixKind::Place,
+                    "助数詞" => SuffixKind::Counter,
+                    "サ変接続" => SuffixKind::VerbalNoun,
+                    "形容動詞語幹" => SuffixKind::AdjectivalNoun,
+                    "副詞可能" => SuffixKind::Adverbial,
+                    "助動詞語幹" => SuffixKind::AuxiliaryVerbStem,
+                    "特殊" => SuffixKind::Special,
+                    _ => SuffixKind::Other,
+                }),
+                "特殊" => NounKind::Special,
+                "ナイ形容詞語幹" => NounKind::NaiAdjectiveStem,
+                "動詞非自立的" => NounKind::VerbLike,
+                "引用文字列" => NounKind::Quotation,
+                "接続詞的" => NounKind::Conjunctive,
+                _ => NounKind::Other,
+            }),
+            "動詞" => PartOfSpeech::Verb(Dependency::from_feature(sub1)),
+            "形容詞" => PartOfSpeech::Adjective(Dependency::from_feature(sub1)),
+            "副詞" => PartOfSpeech::Adverb,
+            "連体詞" => PartOfSpeech::Adnominal,
+            "接続詞" => PartOfSpeech::Conjunction,
+            "助詞" => PartOfSpeech::Particle(match sub1 {
+                "格助詞" => ParticleKind::Case,
+                "係助詞" => ParticleKind::Binding,
+                "副助詞" => ParticleKind::Adverbial,
+                "接続助詞" => ParticleKind::Conjunctive,
+                "終助詞" => ParticleKind::SentenceEnding,
+                "並立助詞" => ParticleKind::Coordinating,
+                "連体化" => ParticleKind::Adnominal,
+                "副助詞／並立助詞／終助詞" => {
+                    ParticleKind::AdverbialCoordinatingSentenceEnding
+                }
+                "副詞化" => ParticleKind::Adverbializer,
+                "特殊" => ParticleKind::Special,
+                _ => ParticleKind::Other,
+            }),
+            "助動詞" => PartOfSpeech::AuxiliaryVerb,
+            "感動詞" => PartOfSpeech::Interjection,
+            "接頭詞" => PartOfSpeech::Prefix(match sub1 {
+                "名詞接続" => PrefixKind::Noun,
+                "動詞接続" => PrefixKind::Verb,
+                "形容詞接続" => PrefixKind::Adjective,
+                "数接続" => PrefixKind::Numeral,
+                _ => PrefixKind::Other,
+            }),
+            "記号" => PartOfSpeech::Symbol(match sub1 {
+                "一般" => SymbolKind::General,
+                "句点" => SymbolKind::Period,
+                "読点" => SymbolKind::Comma,
+                "空白" => SymbolKind::Space,
+                "括弧開" => SymbolKind::OpeningBracket,
+                "括弧閉" => SymbolKind::ClosingBracket,
+                "アルファベット" => SymbolKind::Alphabet,
+                _ => SymbolKind::Other,
+            }),
+            "フィラー" => PartOfSpeech::Filler,
+            _ => PartOfSpeech::Other,
+        }
+    }
+
+    /// Parse the part-of-speech from the first four features of UniDic, mapping its categories
+    /// to the closest ones of IPADIC. For example 代名詞 and 形状詞 (like 綺麗) become nouns,
+    /// 補助記号 and 空白 become symbols and 接尾辞 become noun suffixes or verbs and adjectives
+    /// with [`Dependency::Suffix`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use romanize::{NounKind, PartOfSpeech, SymbolKind};
+    ///
+    /// assert_eq!(
+    ///     PartOfSpeech::from_unidic_features(&["形状詞", "一般", "*", "*"]),
+    ///     PartOfSpeech::Noun(NounKind::AdjectivalNoun),
+    /// );
+    /// assert_eq!(
+    ///     PartOfSpeech::from_unidic_features(&["補助記号", "句点", "*", "*"]),
+    ///     PartOfSpeech::Symbol(SymbolKind::Period),
+    /// );
+    /// ```
+    pub fn from_unidic_features<S: AsRef<str>>(features: &[S]) -> PartOfSpeech {
+        let feature = |i: usize| features.get(i).map(|f| f.as_ref()).unwrap_or("*");
+        let (pos, sub1, sub2, sub3) = (feature(0), feature(1), feature(2), feature(3));
+
+        match pos {
+            "名詞" => PartOfSpeech::Noun(match (sub1, sub2) {
+                ("普通名詞", "サ変可能") | ("普通名詞", "サ変形状詞可能") => {
+                    NounKind::VerbalNoun
+                }
+                ("普通名詞", "形状詞可能") => NounKind::AdjectivalNoun,
+                ("普通名詞", "副詞可能") => NounKind::Adverbial,
+                ("普通名詞", _) => NounKind::Common,
+                ("固有名詞", _) => NounKind::Proper(match (sub2, sub3) {
+                    ("一般", _) => ProperNounKind::General,
+                    ("人名", "姓") => ProperNounKind::Surname,
+                    ("人名", "名") => ProperNounKind::GivenName,
+                    ("人名", _) => ProperNounKind::Person,
+                    ("地名", "国") => ProperNounKind::Country,
+                    ("地名", _) => ProperNounKind::Place,
+                    _ => ProperNounKind::Other,
+                }),
+                ("数詞", _) => NounKind::Numeral,
+                _ => NounKind::Other,
+            }),
+            "代名詞" => PartOfSpeech::Noun(NounKind::Pronoun),
+            "形状詞" => PartOfSpeech::Noun(NounKind::AdjectivalNoun),
+            "動詞" => PartOfSpeech::Verb(Dependency::from_unidic_feature(sub1)),
+            "形容詞" => PartOfSpeech::Adjective(Dependency::from_unidic_feature(sub1)),
+            "副詞" => PartOfSpeech::Adverb,
+            "連体詞" => PartOfSpeech::Adnominal,
+            "接続詞" => PartOfSpeech::Conjunction,
+            "助詞" => PartOfSpeech::Particle(match sub1 {
+                "格助詞" => ParticleKind::Case,
+                "係助詞" => ParticleKind::Binding,
+                "副助詞" => ParticleKind::Adverbial,
+                "接続助詞" => ParticleKind::Conjunctive,
+                "終助詞" => ParticleKind::SentenceEnding,
+                _ => ParticleKind::Other,
+            }),
+            "助動詞" => PartOfSpeech::AuxiliaryVerb,
+            "感動詞" if sub1 == "フィラー" => PartOfSpeech::Filler,
+            "感動詞" => PartOfSpeech::Interjection,
+            "接頭辞" => PartOfSpeech::Prefix(PrefixKind::Other),
+            "接尾辞" => match sub1 {
+                "名詞的" => PartOfSpeech::Noun(NounKind::Suffix(match sub2 {
+                    "一般" => SuffixKind::General,
+                    "助数詞" => SuffixKind::Counter,
+                    "サ変可能" => SuffixKind::VerbalNoun,
+                    "形状詞可能" => SuffixKind::AdjectivalNoun,
+                    "副詞可能" => SuffixKind::Adverbial,
+                    _ => SuffixKind::Other,
+                })),
+                "形状詞的" => PartOfSpeech::Noun(NounKind::Suffix(SuffixKind::AdjectivalNoun)),
+                "動詞的" => PartOfSpeech::Verb(Dependency::Suffix),
+                "形容詞的" => PartOfSpeech::Adjective(Dependency::Suffix),
+                _ => PartOfSpeech::Noun(NounKind::Suffix(SuffixKind::Other)),
+            },
+            "補助記号" => PartOfSpeech::Symbol(match sub1 {
+                "一般" => SymbolKind::General,
+                "句点" => SymbolKind::Period,
+                "読点" => SymbolKind::Comma,
+                "括弧開" => SymbolKind::OpeningBracket,
+                "括弧閉" => SymbolKind::ClosingBracket,
+                _ => SymbolKind::Other,
+            }),
+            "記号" => PartOfSpeech::Symbol(match sub1 {
+                "一般" => SymbolKind::General,
+                "文字" => SymbolKind::Alphabet,
+                _ => SymbolKind::Other,
+            }),
+            "空白" => PartOfSpeech::Symbol(SymbolKind::Space),
+            _ => PartOfSpeech::Other,
+        }
+    }
+
+    /// Whether this is a noun (名詞).
+    pub fn is_noun(self) -> bool {
+        matches!(self, PartOfSpeech::Noun(_))
+    }
+
+    /// Whether this is a particle (助詞).
+    pub fn is_particle(self) -> bool {
+        matches!(self, PartOfSpeech::Particle(_))
+    }
+
+    /// Whether this is a symbol (記号) like punctuation.
+    pub fn is_symbol(self) -> bool {
+        matches!(self, PartOfSpeech::Symbol(_))
+    }
+}
+
+impl Dependency {
+    fn from_feature(feature: &str) -> Dependency {
+        match feature {
+            "自立" => Dependency::Independent,
+            "非自立" => Dependency::Dependent,
+            "接尾" => Dependency::Suffix,
+            _ => Dependency::Other,
+        }
+    }
+
+    fn from_unidic_feature(feature: &str) -> Dependency {
+        match feature {
+            "一般" => Dependency::Independent,
+            "非自立可能" => Dependency::Dependent,
+            _ => Dependency::Other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_features() {
+        assert_eq!(
+            PartOfSpeech::from_features(&["名詞", "接尾", "人名", "*"]),
+            PartOfSpeech::Noun(NounKind::Suffix(SuffixKind::Person)),
+        );
+        assert_eq!(
+            PartOfSpeech::from_features(&["動詞", "非自立", "*", "*"]),
+            PartOfSpeech::Verb(Dependency::Dependent),
+        );
+        assert_eq!(
+            PartOfSpeech::from_features(&["助詞", "係助詞"]),
+            PartOfSpeech::Particle(ParticleKind::Binding),
+        );
+        assert_eq!(
+            PartOfSpeech::from_features(&["記号", "括弧開", "*", "*"]),
+            PartOfSpeech::Symbol(SymbolKind::OpeningBracket),
+        );
+        assert_eq!(
+            PartOfSpeech::from_features::<&str>(&[]),
+            PartOfSpeech::Other
+        );
+    }
+
+    #[test]
+    fn from_unidic_features() {
+        assert_eq!(
+            PartOfSpeech::from_unidic_features(&["名詞", "固有名詞", "地名", "一般"]),
+            PartOfSpeech::Noun(NounKind::Proper(ProperNounKind::Place)),
+        );
+        assert_eq!(
+            PartOfSpeech::from_unidic_features(&["名詞", "普通名詞", "サ変可能", "*"]),
+            PartOfSpeech::Noun(NounKind::VerbalNoun),
+        );
+        assert_eq!(
+            PartOfSpeech::from_unidic_features(&["動詞", "非自立可能", "*", "*"]),
+            PartOfSpeech::Verb(Dependency::Dependent),
+        );
+        assert_eq!(
+            PartOfSpeech::from_unidic_features(&["接尾辞", "名詞的", "助数詞", "*"]),
+            PartOfSpeech::Noun(NounKind::Suffix(SuffixKind::Counter)),
+        );
+        assert_eq!(
+            PartOfSpeech::from_unidic_features(&["感動詞", "フィラー", "*", "*"]),
+            PartOfSpeech::Filler,
+        );
+        assert_eq!(
+            PartOfSpeech::from_unidic_features(&["空白", "*", "*", "*"]),
+            PartOfSpeech::Symbol(SymbolKind::Space),
+        );
+    }
+}
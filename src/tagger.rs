@@ -0,0 +1,921 @@
+//! Tagger for igo dictionaries.
+//!
+//! This is a port of igo's tagger (`net.reduls.igo.Tagger`) working on the contents of the
+//! dictionary files so they don't have to be read from a directory. It finds the same morphemes as
+//! igo and can additionally add the words of a user dictionary to the lattice.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use error::RomanizeError;
+use tokenizer::split_features;
+
+/// Check code of nodes terminating a key in the trie.
+const TERMINATE_CODE: u16 = 0;
+
+/// Cost of user words which don't specify one. Low enough for them to win over several words of
+/// the dictionary spanning the same text, but not so low that they split longer words.
+pub const USER_WORD_COST: i16 = 2000;
+
+/// A morpheme found by the [`Tagger`].
+pub struct Morpheme<'a> {
+    /// Slice of the input.
+    pub surface: &'a str,
+    pub feature: String,
+    /// Whether this is one of the words added with [`Tagger::add_words`].
+    pub user: bool,
+}
+
+/// A word to add to the dictionary, see [`Tagger::add_words`].
+pub struct UserWord<'a> {
+    pub surface: &'a str,
+    pub feature: &'a str,
+    /// Context ids and cost as in MeCab dictionaries. Missing ids are taken from a word of the
+    /// dictionary with the same part-of-speech, a missing cost defaults to [`USER_WORD_COST`].
+    pub left_id: Option<i16>,
+    pub right_id: Option<i16>,
+    pub cost: Option<i16>,
+    /// Line of the user dictionary defining the word, used in errors.
+    pub line: usize,
+}
+
+/// Viterbi tagger using the files of an igo dictionary.
+pub struct Tagger {
+    trie: Trie,
+    words: Words,
+    matrix: Matrix,
+    categories: Categories,
+    user_words: UserWords,
+}
+
+impl Tagger {
+    /// Load the dictionary from the contents of its files, keyed by file name.
+    pub fn from_files(files: &HashMap<String, Vec<u8>>) -> Result<Tagger, RomanizeError> {
+        let file = |name: &'static str| {
+            files
+                .get(name)
+                .map(|bytes| Reader { name, bytes })
+                .ok_or_else(|| RomanizeError::MissingDictionaryFile(PathBuf::from(name)))
+        };
+
+        let trie = Trie::read(file("word2id")?)?;
+        let words = Words::read(file("word.inf")?, file("word.dat")?, file("word.ary.idx")?)?;
+        let matrix = Matrix::read(file("matrix.bin")?)?;
+        let categories = Categories::read(file("char.category")?, file("code2category")?)?;
+
+        // Validate the ids once so tagging can't index out of bounds. The ids found in the trie
+        // are checked while tagging as they're only known for its leaves.
+        if let Some(category) = categories
+            .by_id
+            .iter()
+            .flatten()
+            .find(|category| category.id as usize + 1 >= words.indices.len())
+        {
+            return malformed(
+                "char.category",
+                format!("category id {} is out of range", category.id),
+            );
+        }
+        let (left_ids, right_ids) = (words.left_ids.iter(), words.right_ids.iter());
+        if let Some((left, right)) = left_ids
+            .zip(right_ids)
+            .find(|&(&left, &right)| !matrix.contains(right, left))
+        {
+            return malformed(
+                "word.inf",
+                format!("context ids {} and {} are out of range", left, right),
+            );
+        }
+        if !matrix.contains(0, 0) {
+            return malformed("matrix.bin", "matrix is empty".to_string());
+        }
+
+        Ok(Tagger {
+            trie,
+            words,
+            matrix,
+            categories,
+            user_words: UserWords::default(),
+        })
+    }
+
+    /// Add `words` to the lattice so they compete with the words of the dictionary. They replace
+    /// the words of the dictionary as well as earlier words with the same surface.
+    ///
+    /// Fails with [`RomanizeError::InvalidUserDictionary`] if the context ids of a word are out of
+    /// range.
+    pub fn add_words<'a, I>(&mut self, words: I) -> Result<(), RomanizeError>
+    where
+        I: IntoIterator<Item = UserWord<'a>>,
+    {
+        let words = words.into_iter().collect::<Vec<_>>();
+        let contexts = if words
+            .iter()
+            .any(|word| word.left_id.is_none() || word.right_id.is_none())
+        {
+            self.contexts_by_pos()
+        } else {
+            HashMap::new()
+        };
+
+        for word in words {
+            let surface = word.surface.encode_utf16().collect::<Vec<_>>();
+            if surface.is_empty() {
+                continue;
+            }
+            let (left_id, right_id) = match (word.left_id, word.right_id) {
+                (Some(left_id), Some(right_id)) => (left_id, right_id),
+                (left_id, right_id) => {
+                    let (similar_left, similar_right) = self.similar_context(&contexts, &word);
+                    (
+                        left_id.unwrap_or(similar_left),
+                        right_id.unwrap_or(similar_right),
+                    )
+                }
+            };
+            if !self.matrix.contains(right_id, left_id) {
+                return Err(RomanizeError::InvalidUserDictionary(
+                    word.line,
+                    format!("context ids {} and {} are out of range", left_id, right_id),
+                ));
+            }
+
+            self.user_words.max_len = self.user_words.max_len.max(surface.len());
+            let entry = UserEntry {
+                feature: word.feature.to_string(),
+                left_id,
+                right_id,
+                cost: word.cost.unwrap_or(USER_WORD_COST),
+            };
+            match self.user_words.ids.get(&surface) {
+                Some(&id) => self.user_words.entries[id] = entry,
+                None => {
+                    self.user_words
+                        .ids
+                        .insert(surface, self.user_words.entries.len());
+                    self.user_words.entries.push(entry);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The context ids of the words of the dictionary by their part-of-speech and conjugation
+    /// (the first six fields of the feature) as well as every prefix of those fields. The first
+    /// word wins if several words share them.
+    fn contexts_by_pos(&self) -> HashMap<String, (i16, i16)> {
+        let mut contexts = HashMap::new();
+        let mut seen = HashSet::new();
+        let ids = self.words.left_ids.iter().zip(&self.words.right_ids);
+        for (word_id, (&left_id, &right_id)) in ids.enumerate().take(self.words.len()) {
+            if !seen.insert((left_id, right_id)) {
+                continue;
+            }
+            let feature = self.words.feature(word_id);
+            let fields = split_features(&feature);
+            for len in 1..=fields.len().min(6) {
+                contexts
+                    .entry(fields[..len].join(","))
+                    .or_insert((left_id, right_id));
+            }
+        }
+        contexts
+    }
+
+    /// The context ids of a dictionary word whose feature shares the most leading fields with the
+    /// one of `word`, see [`Tagger::contexts_by_pos`]. Falls back to the ids of the unknown words
+    /// of its first character.
+    fn similar_context(
+        &self,
+        contexts: &HashMap<String, (i16, i16)>,
+        word: &UserWord,
+    ) -> (i16, i16) {
+        let fields = split_features(word.feature);
+        (1..=fields.len().min(6))
+            .rev()
+            .filter_map(|len| contexts.get(&fields[..len].join(",")))
+            .next()
+            .cloned()
+            .unwrap_or_else(|| {
+                let first = word.surface.encode_utf16().next().unwrap_or(0);
+                let id = self.categories.category(first).id as usize;
+                // Checked while loading
+                let word_id = self.words.indices[id] as usize;
+                (self.words.left_ids[word_id], self.words.right_ids[word_id])
+            })
+    }
+
+    /// Split `text` into morphemes.
+    pub fn parse<'a>(&self, text: &'a str) -> Result<Vec<Morpheme<'a>>, RomanizeError> {
+        let units = text.encode_utf16().collect::<Vec<_>>();
+        // Byte offset of every UTF-16 offset. The second half of a surrogate pair maps to the end
+        // of its character, so a morpheme ending within it keeps the whole character.
+        let mut offsets = Vec::with_capacity(units.len() + 1);
+        for (i, c) in text.char_indices() {
+            offsets.push(i);
+            if c.len_utf16() == 2 {
+                offsets.push(i + c.len_utf8());
+            }
+        }
+        offsets.push(text.len());
+
+        let mut morphemes = Vec::new();
+        for node in self.lattice(&units)? {
+            let (start, end) = (offsets[node.start], offsets[node.start + node.len]);
+            if start < end {
+                // User words are numbered after the words of the dictionary
+                let user = node.word_id.checked_sub(self.words.len());
+                morphemes.push(Morpheme {
+                    surface: &text[start..end],
+                    feature: match user {
+                        Some(id) => self.user_words.entries[id].feature.clone(),
+                        None => self.words.feature(node.word_id),
+                    },
+                    user: user.is_some(),
+                });
+            }
+        }
+        Ok(morphemes)
+    }
+
+    /// Find the cheapest path through the lattice of all words in `text`.
+    fn lattice(&self, text: &[u16]) -> Result<Vec<Node>, RomanizeError> {
+        let bos = Node {
+            word_id: 0,
+            start: 0,
+            len: 0,
+            cost: 0,
+            left_id: 0,
+            right_id: 0,
+            is_space: false,
+            prev: None,
+        };
+        // All nodes, referenced by index
+        let mut nodes = vec![bos];
+        // The nodes ending at each position
+        let mut ends = vec![None; text.len() + 1];
+        ends[0] = Some(vec![0]);
+
+        let mut found = Vec::new();
+        for start in 0..text.len() {
+            let prevs = match ends[start].take() {
+                Some(prevs) => prevs,
+                None => continue,
+            };
+
+            found.clear();
+            self.trie.each_common_prefix(text, start, |len, id| {
+                // User words replace the words of the dictionary with the same surface
+                if !self.user_words.ids.contains_key(&text[start..start + len]) {
+                    self.words.push_nodes(&mut found, id, start, len, false);
+                }
+            });
+            self.push_user_nodes(text, start, &mut found);
+            self.search_unknown(text, start, &mut found);
+
+            for mut node in found.drain(..) {
+                let end = ends[start + node.len].get_or_insert_with(Vec::new);
+                if node.is_space {
+                    // Spaces are skipped by connecting the following words to the preceding ones
+                    end.extend_from_slice(&prevs);
+                } else {
+                    self.connect(&mut node, &prevs, &nodes);
+                    end.push(nodes.len());
+                    nodes.push(node);
+                }
+            }
+        }
+
+        let mut eos = nodes[0].clone();
+        let prevs = ends[text.len()]
+            .take()
+            .ok_or_else(|| RomanizeError::Dictionary("no path through the lattice".to_string()))?;
+        self.connect(&mut eos, &prevs, &nodes);
+
+        let mut path = Vec::new();
+        let mut cur = eos.prev;
+        while let Some(i) = cur.filter(|&i| i != 0) {
+            path.push(nodes[i].clone());
+            cur = nodes[i].prev;
+        }
+        path.reverse();
+        Ok(path)
+    }
+
+    /// Add the user words starting at `start`.
+    fn push_user_nodes(&self, text: &[u16], start: usize, found: &mut Vec<Node>) {
+        let max_len = self.user_words.max_len.min(text.len() - start);
+        for len in 1..=max_len {
+            if let Some(&id) = self.user_words.ids.get(&text[start..start + len]) {
+                let entry = &self.user_words.entries[id];
+                found.push(Node {
+                    word_id: self.words.len() + id,
+                    start,
+                    len,
+                    cost: i32::from(entry.cost),
+                    left_id: entry.left_id,
+                    right_id: entry.right_id,
+                    is_space: false,
+                    prev: None,
+                });
+            }
+        }
+    }
+
+    /// Add the unknown words starting at `start` based on the category of its character.
+    fn search_unknown(&self, text: &[u16], start: usize, found: &mut Vec<Node>) {
+        let ch = text[start];
+        let category = self.categories.category(ch);
+        if !found.is_empty() && !category.invoke {
+            return;
+        }
+
+        let is_space = category.id == self.categories.space_id;
+        let id = category.id as usize;
+        let limit = text.len().min(start + category.length as usize);
+        let mut i = start;
+        while i < limit {
+            self.words
+                .push_nodes(found, id, start, i - start + 1, is_space);
+            if i + 1 != limit && !self.categories.compatible(ch, text[i + 1]) {
+                return;
+            }
+            i += 1;
+        }
+
+        if category.group && i < text.len() {
+            while i < text.len() {
+                if !self.categories.compatible(ch, text[i]) {
+                    self.words.push_nodes(found, id, start, i - start, is_space);
+                    return;
+                }
+                i += 1;
+            }
+            self.words
+                .push_nodes(found, id, start, text.len() - start, is_space);
+        }
+    }
+
+    /// Connect `node` to the cheapest of `prevs`. The first one wins ties.
+    fn connect(&self, node: &mut Node, prevs: &[usize], nodes: &[Node]) {
+        let cost = |prev: &Node| {
+            prev.cost
+                .wrapping_add(i32::from(self.matrix.cost(prev.right_id, node.left_id)))
+        };
+        let mut best = prevs[0];
+        let mut min = cost(&nodes[best]);
+        for &prev in &prevs[1..] {
+            let cost = cost(&nodes[prev]);
+            if cost < min {
+                best = prev;
+                min = cost;
+            }
+        }
+        node.prev = Some(best);
+        node.cost = node.cost.wrapping_add(min);
+    }
+}
+
+/// A word in the lattice.
+#[derive(Clone)]
+struct Node {
+    word_id: usize,
+    /// Position and length in UTF-16 code units.
+    start: usize,
+    len: usize,
+    /// Cost of the cheapest path up to and including this word.
+    cost: i32,
+    left_id: i16,
+    right_id: i16,
+    is_space: bool,
+    prev: Option<usize>,
+}
+
+/// Words added with [`Tagger::add_words`].
+#[derive(Default)]
+struct UserWords {
+    /// Index into `entries` by surface in UTF-16.
+    ids: HashMap<Vec<u16>, usize>,
+    entries: Vec<UserEntry>,
+    /// Length of the longest surface in UTF-16 code units.
+    max_len: usize,
+}
+
+struct UserEntry {
+    feature: String,
+    left_id: i16,
+    right_id: i16,
+    cost: i16,
+}
+
+/// Double-array trie mapping surfaces to trie ids (`word2id`).
+struct Trie {
+    begs: Vec<i32>,
+    base: Vec<i32>,
+    lens: Vec<i16>,
+    chck: Vec<u16>,
+    tail: Vec<u16>,
+}
+
+impl Trie {
+    fn read(mut file: Reader) -> Result<Trie, RomanizeError> {
+        let node_size = file.len()?;
+        let key_set_size = file.len()?;
+        let tail_len = file.len()?;
+        let trie = Trie {
+            begs: file.i32s(key_set_size)?,
+            base: file.i32s(node_size)?,
+            lens: file.i16s(key_set_size)?,
+            chck: file.u16s(node_size)?,
+            tail: file.u16s(tail_len)?,
+        };
+        for (&beg, &len) in trie.begs.iter().zip(&trie.lens) {
+            if beg < 0 || len < 0 || beg as usize + len as usize > tail_len {
+                return malformed("word2id", "tail out of range".to_string());
+            }
+        }
+        Ok(trie)
+    }
+
+    /// Call `f` with the length and id of every key which is a prefix of `text[start..]`.
+    fn each_common_prefix<F: FnMut(usize, usize)>(&self, text: &[u16], start: usize, mut f: F) {
+        let base = |i: usize| self.base.get(i).cloned();
+        let mut node = match base(0) {
+            Some(node) => node,
+            None => return,
+        };
+        let mut pos = start;
+        let mut read = || {
+            let code = text.get(pos).cloned().unwrap_or(TERMINATE_CODE);
+            pos += 1;
+            code
+        };
+
+        let mut offset = 0;
+        let mut code = read();
+        loop {
+            if node < 0 {
+                return;
+            }
+            let terminal = node as usize;
+            if self.chck.get(terminal) == Some(&TERMINATE_CODE) {
+                if let Some(id) = base(terminal).and_then(id) {
+                    f(offset, id);
+                }
+                if code == TERMINATE_CODE {
+                    return;
+                }
+            }
+
+            let idx = node as usize + code as usize;
+            node = match base(idx) {
+                Some(node) if self.chck[idx] == code => node,
+                _ => return,
+            };
+            if node >= 0 {
+                code = read();
+                offset += 1;
+                continue;
+            }
+
+            if let Some(id) = id(node).filter(|&id| id < self.begs.len()) {
+                let (beg, len) = (self.begs[id] as usize, self.lens[id] as usize);
+                let rest = text.get(start + offset + 1..).unwrap_or(&[]);
+                if rest.starts_with(&self.tail[beg..beg + len]) {
+                    f(offset + len + 1, id);
+                }
+            }
+            return;
+        }
+    }
+}
+
+/// The id stored in a base of a leaf.
+fn id(base: i32) -> Option<usize> {
+    if base < 0 {
+        Some((-(i64::from(base)) - 1) as usize)
+    } else {
+        None
+    }
+}
+
+/// The entries of the dictionary (`word.inf`, `word.dat` and `word.ary.idx`).
+struct Words {
+    data_offsets: Vec<i32>,
+    left_ids: Vec<i16>,
+    right_ids: Vec<i16>,
+    costs: Vec<i16>,
+    data: Vec<u16>,
+    /// The first entry of each trie id.
+    indices: Vec<i32>,
+}
+
+impl Words {
+    fn read(
+        mut info: Reader,
+        mut data: Reader,
+        mut indices: Reader,
+    ) -> Result<Words, RomanizeError> {
+        let count = info.bytes.len() / 10;
+        let words = Words {
+            data_offsets: info.i32s(count)?,
+            left_ids: info.i16s(count)?,
+            right_ids: info.i16s(count)?,
+            costs: info.i16s(count)?,
+            data: data.u16s(data.bytes.len() / 2)?,
+            indices: indices.i32s(indices.bytes.len() / 4)?,
+        };
+
+        if words.data_offsets.windows(2).any(|offsets| {
+            offsets[0] < 0 || offsets[0] > offsets[1] || offsets[1] as usize > words.data.len()
+        }) {
+            return malformed("word.inf", "feature offsets out of range".to_string());
+        }
+        if words.indices.windows(2).any(|indices| {
+            indices[0] < 0 || indices[0] > indices[1] || indices[1] as usize >= count
+        }) {
+            return malformed("word.ary.idx", "word indices out of range".to_string());
+        }
+        Ok(words)
+    }
+
+    /// Add a node for every entry of the trie id `id`.
+    fn push_nodes(
+        &self,
+        found: &mut Vec<Node>,
+        id: usize,
+        start: usize,
+        len: usize,
+        is_space: bool,
+    ) {
+        let range = match (self.indices.get(id), self.indices.get(id + 1)) {
+            (Some(&start), Some(&end)) => start as usize..end as usize,
+            _ => return,
+        };
+        found.extend(range.map(|i| Node {
+            word_id: i,
+            start,
+            len,
+            cost: i32::from(self.costs[i]),
+            left_id: self.left_ids[i],
+            right_id: self.right_ids[i],
+            is_space,
+            prev: None,
+        }));
+    }
+
+    /// Number of entries. The last offset only marks the end of the features.
+    fn len(&self) -> usize {
+        self.data_offsets.len().saturating_sub(1)
+    }
+
+    fn feature(&self, word_id: usize) -> String {
+        let range = self.data_offsets[word_id] as usize..self.data_offsets[word_id + 1] as usize;
+        String::from_utf16_lossy(&self.data[range])
+    }
+}
+
+/// Connection costs between words (`matrix.bin`).
+struct Matrix {
+    left_size: usize,
+    right_size: usize,
+    costs: Vec<i16>,
+}
+
+impl Matrix {
+    fn read(mut file: Reader) -> Result<Matrix, RomanizeError> {
+        let left_size = file.len()?;
+        let right_size = file.len()?;
+        let costs = file.i16s(left_size * right_size)?;
+        Ok(Matrix {
+            left_size,
+            right_size,
+            costs,
+        })
+    }
+
+    /// Whether the cost of connecting `left` to `right` is known (see [`Matrix::cost`]).
+    fn contains(&self, left: i16, right: i16) -> bool {
+        left >= 0
+            && right >= 0
+            && (left as usize) < self.left_size
+            && (right as usize) < self.right_size
+    }
+
+    /// Cost of connecting a word with the right context id `left` to one with the left context
+    /// id `right`.
+    fn cost(&self, left: i16, right: i16) -> i16 {
+        self.costs[right as usize * self.left_size + left as usize]
+    }
+}
+
+/// A character category (`char.category`).
+#[derive(Clone, Copy)]
+struct Category {
+    /// Trie id of the unknown words of this category.
+    id: i32,
+    length: i32,
+    invoke: bool,
+    group: bool,
+}
+
+/// Character categories and the category of every UTF-16 code unit (`code2category`).
+struct Categories {
+    by_id: Vec<Option<Category>>,
+    char_ids: Vec<i32>,
+    masks: Vec<i32>,
+    space_id: i32,
+}
+
+impl Categories {
+    fn read(
+        mut categories: Reader,
+        mut code2category: Reader,
+    ) -> Result<Categories, RomanizeError> {
+        let values = categories.i32s(categories.bytes.len() / 4)?;
+        let mut by_id = Vec::new();
+        for category in values.chunks(4).filter(|category| category.len() == 4) {
+            if category[0] < 0 || category[1] < 0 {
+                return malformed("char.category", "negative id or length".to_string());
+            }
+            let id = category[0] as usize;
+            if id >= by_id.len() {
+                by_id.resize(id + 1, None);
+            }
+            by_id[id] = Some(Category {
+                id: category[0],
+                length: category[1],
+                invoke: category[2] == 1,
+                group: category[3] == 1,
+            });
+        }
+
+        let char_ids = code2category.i32s(0x10000)?;
+        let masks = code2category.i32s(0x10000)?;
+        if let Some(id) = char_ids
+            .iter()
+            .find(|&&id| id < 0 || by_id.get(id as usize).map(Option::is_none).unwrap_or(true))
+        {
+            return malformed("code2category", format!("category {} is undefined", id));
+        }
+        let space_id = char_ids[usize::from(b' ')];
+
+        Ok(Categories {
+            by_id,
+            char_ids,
+            masks,
+            space_id,
+        })
+    }
+
+    fn category(&self, ch: u16) -> Category {
+        // Checked while loading
+        self.by_id[self.char_ids[ch as usize] as usize].unwrap()
+    }
+
+    /// Whether `b` may be part of an unknown word starting with `a`.
+    fn compatible(&self, a: u16, b: u16) -> bool {
+        self.masks[a as usize] & self.masks[b as usize] != 0
+    }
+}
+
+/// Reads little-endian values from a dictionary file.
+struct Reader<'a> {
+    name: &'static str,
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], RomanizeError> {
+        if len > self.bytes.len() {
+            return malformed(self.name, "unexpected end of file".to_string());
+        }
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    /// A non-negative i32 used as size.
+    fn len(&mut self) -> Result<usize, RomanizeError> {
+        let len = self.i32s(1)?[0];
+        if len < 0 {
+            return malformed(self.name, format!("negative size {}", len));
+        }
+        Ok(len as usize)
+    }
+
+    fn i32s(&mut self, count: usize) -> Result<Vec<i32>, RomanizeError> {
+        let bytes = self.take(count.saturating_mul(4))?;
+        Ok(bytes
+            .chunks(4)
+            .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect())
+    }
+
+    fn i16s(&mut self, count: usize) -> Result<Vec<i16>, RomanizeError> {
+        let bytes = self.take(count.saturating_mul(2))?;
+        Ok(bytes
+            .chunks(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect())
+    }
+
+    fn u16s(&mut self, count: usize) -> Result<Vec<u16>, RomanizeError> {
+        let bytes = self.take(count.saturating_mul(2))?;
+        Ok(bytes
+            .chunks(2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .collect())
+    }
+}
+
+fn malformed<T>(name: &str, reason: String) -> Result<T, RomanizeError> {
+    Err(RomanizeError::MalformedDictionaryFile(
+        PathBuf::from(name),
+        reason,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dictionary::{DictionaryArchive, LoadStats};
+
+    fn tagger() -> Tagger {
+        let archive = DictionaryArchive::from_bytes(
+            &include_bytes!("../tests/fixtures/mini-ipadic/igo.zip")[..],
+        );
+        Tagger::from_files(&archive.read_files(&mut LoadStats::default()).unwrap()).unwrap()
+    }
+
+    fn parse(tagger: &Tagger, text: &str) -> Vec<(String, String)> {
+        tagger
+            .parse(text)
+            .unwrap()
+            .into_iter()
+            .map(|morpheme| (morpheme.surface.to_string(), morpheme.feature))
+            .collect()
+    }
+
+    #[test]
+    fn same_as_igo() {
+        // Output of igo 0.4.5 (net.reduls.igo.bin.Igo) for the fixture
+        let expected = [
+            (
+                "東京都の本屋へ行った。",
+                vec![
+                    (
+                        "東京",
+                        "名詞,固有名詞,地域,一般,*,*,東京,トウキョウ,トーキョー",
+                    ),
+                    ("都", "名詞,接尾,地域,*,*,*,都,ト,ト"),
+                    ("の", "助詞,連体化,*,*,*,*,の,ノ,ノ"),
+                    ("本屋", "名詞,一般,*,*,*,*,本屋,ホンヤ,ホンヤ"),
+                    ("へ", "助詞,格助詞,一般,*,*,*,へ,ヘ,エ"),
+                    (
+                        "行っ",
+                        "動詞,自立,*,*,五段・カ行促音便,連用タ接続,行く,イッ,イッ",
+                    ),
+                    ("た", "助動詞,*,*,*,特殊・タ,基本形,た,タ,タ"),
+                    ("。", "記号,句点,*,*,*,*,。,。,。"),
+                ],
+            ),
+            (
+                "田中さんはミクロの単位です",
+                vec![
+                    ("田中", "名詞,固有名詞,人名,姓,*,*,田中,タナカ,タナカ"),
+                    ("さん", "名詞,接尾,人名,*,*,*,さん,サン,サン"),
+                    ("は", "助詞,係助詞,*,*,*,*,は,ハ,ワ"),
+                    ("ミクロ", "名詞,一般,*,*,*,*,ミクロ,ミクロ,ミクロ"),
+                    ("の", "助詞,連体化,*,*,*,*,の,ノ,ノ"),
+                    ("単位", "名詞,一般,*,*,*,*,単位,タンイ,タンイ"),
+                    ("です", "助動詞,*,*,*,特殊・デス,基本形,です,デス,デス"),
+                ],
+            ),
+            (
+                "今日  ABC 123、ミクは青い空",
+                vec![
+                    ("今日", "名詞,副詞可能,*,*,*,*,今日,キョウ,キョー"),
+                    ("ABC", "名詞,一般,*,*,*,*,*"),
+                    ("123", "名詞,数,*,*,*,*,*"),
+                    ("、", "記号,読点,*,*,*,*,、,、,、"),
+                    ("ミク", "名詞,固有名詞,人名,名,*,*,ミク,ミク,ミク"),
+                    ("は", "助詞,係助詞,*,*,*,*,は,ハ,ワ"),
+                    (
+                        "青い",
+                        "形容詞,自立,*,*,形容詞・アウオ段,基本形,青い,アオイ,アオイ",
+                    ),
+                    ("空", "名詞,一般,*,*,*,*,空,ソラ,ソラ"),
+                ],
+            ),
+            (
+                "京都東京東 境界ｘ〇",
+                vec![
+                    ("京都", "名詞,固有名詞,地域,一般,*,*,京都,キョウト,キョート"),
+                    (
+                        "東京",
+                        "名詞,固有名詞,地域,一般,*,*,東京,トウキョウ,トーキョー",
+                    ),
+                    ("東", "名詞,一般,*,*,*,*,東,ヒガシ,ヒガシ"),
+                    ("境界", "名詞,一般,*,*,*,*,境界,キョウカイ,キョーカイ"),
+                    ("ｘ", "名詞,一般,*,*,*,*,*"),
+                    ("〇", "記号,一般,*,*,*,*,*"),
+                ],
+            ),
+        ];
+
+        let tagger = tagger();
+        for (text, morphemes) in &expected {
+            let morphemes = morphemes
+                .iter()
+                .map(|&(surface, feature)| (surface.to_string(), feature.to_string()))
+                .collect::<Vec<_>>();
+            assert_eq!(parse(&tagger, text), morphemes, "{}", text);
+        }
+        assert!(parse(&tagger, "").is_empty());
+        // Surrogate pairs are kept together by the DEFAULT category
+        assert_eq!(
+            parse(&tagger, "𠮷野家"),
+            vec![
+                ("𠮷".to_string(), "記号,一般,*,*,*,*,*".to_string()),
+                ("野家".to_string(), "名詞,一般,*,*,*,*,*".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn user_words() {
+        let word = |surface, feature, line| UserWord {
+            surface,
+            feature,
+            left_id: None,
+            right_id: None,
+            cost: None,
+            line,
+        };
+        let mut tagger = tagger();
+        tagger
+            .add_words(vec![
+                word(
+                    "ミクロの",
+                    "名詞,固有名詞,一般,*,*,*,ミクロの,ミクロノ,ミクロノ",
+                    1,
+                ),
+                word("東京", "名詞,固有名詞,地域,一般,*,*,東京,ヒガシキョウ", 2),
+            ])
+            .unwrap();
+
+        let morphemes = tagger.parse("ミクロの単位は東京都").unwrap();
+        let found = morphemes
+            .iter()
+            .map(|morpheme| (morpheme.surface, morpheme.user))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                ("ミクロの", true),
+                ("単位", false),
+                ("は", false),
+                ("東京", true),
+                ("都", false),
+            ]
+        );
+        assert_eq!(
+            morphemes[3].feature,
+            "名詞,固有名詞,地域,一般,*,*,東京,ヒガシキョウ"
+        );
+
+        let invalid = UserWord {
+            left_id: Some(1000),
+            ..word("空", "名詞,一般,*,*,*,*,空,ソラ,ソラ", 3)
+        };
+        match tagger.add_words(vec![invalid]) {
+            Err(RomanizeError::InvalidUserDictionary(3, _)) => {}
+            _ => panic!("expected out of range context ids"),
+        }
+    }
+
+    #[test]
+    fn missing_file() {
+        let archive = DictionaryArchive::from_bytes(
+            &include_bytes!("../tests/fixtures/mini-ipadic/igo.zip")[..],
+        );
+        let mut files = archive.read_files(&mut LoadStats::default()).unwrap();
+        files.remove("matrix.bin");
+        match Tagger::from_files(&files) {
+            Err(RomanizeError::MissingDictionaryFile(path)) => {
+                assert_eq!(path, PathBuf::from("matrix.bin"))
+            }
+            _ => panic!("expected missing matrix.bin"),
+        }
+
+        files = archive.read_files(&mut LoadStats::default()).unwrap();
+        files.get_mut("word2id").unwrap().truncate(20);
+        match Tagger::from_files(&files) {
+            Err(RomanizeError::MalformedDictionaryFile(path, _)) => {
+                assert_eq!(path, PathBuf::from("word2id"))
+            }
+            _ => panic!("expected malformed word2id"),
+        }
+    }
+}
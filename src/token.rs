@@ -0,0 +1,40 @@
+use std::ops::Range;
+
+use pos::PartOfSpeech;
+use tokenizer::Morpheme;
+
+/// A single word of the input as found by the tagger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    /// The word as it appears in the input.
+    pub surface: String,
+    /// Byte range of the word in the input.
+    pub range: Range<usize>,
+    /// Part-of-speech including its subdivisions.
+    pub pos: PartOfSpeech,
+    /// Dictionary form of the word, e.g. 食べる for 食べ.
+    pub base_form: Option<String>,
+    /// Reading in katakana as written, e.g. トウキョウ for 東京.
+    pub reading: Option<String>,
+    /// Pronunciation in katakana, e.g. トーキョー for 東京.
+    pub pronunciation: Option<String>,
+    /// The romanized reading. `None` for symbols and words without a reading.
+    pub romaji: Option<String>,
+    /// Whether `romaji` comes from the [`Overrides`](::Overrides) and is used as is.
+    pub overridden: bool,
+}
+
+impl Token {
+    pub(crate) fn new(morpheme: Morpheme, range: Range<usize>) -> Token {
+        Token {
+            surface: morpheme.surface.to_string(),
+            range,
+            pos: morpheme.pos,
+            base_form: morpheme.base_form,
+            reading: morpheme.reading,
+            pronunciation: morpheme.pronunciation,
+            romaji: None,
+            overridden: false,
+        }
+    }
+}
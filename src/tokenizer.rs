@@ -0,0 +1,395 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use dictionary::{self, DictionaryArchive, DictionarySource, LoadStats};
+use error::RomanizeError;
+use pos::{NounKind, PartOfSpeech, SuffixKind};
+use tagger;
+use user_dictionary::UserDictionary;
+
+/// A morphological analyzer splitting the input into words with their readings.
+///
+/// [`IgoTokenizer`] is used by default; implement this to use another analyzer with
+/// [`RomanizerBuilder::build_with_tokenizer`](::RomanizerBuilder::build_with_tokenizer).
+///
+/// # Examples
+///
+/// ```
+/// use romanize::{Morpheme, PartOfSpeech, RomanizeError, Romanizer, Tokenizer};
+///
+/// /// Treats the whole input as a single katakana word.
+/// struct Katakana;
+///
+/// impl Tokenizer for Katakana {
+///     fn tokenize<'a>(&self, input: &'a str) -> Result<Vec<Morpheme<'a>>, RomanizeError> {
+///         Ok(vec![Morpheme {
+///             reading: Some(input.to_string()),
+///             pronunciation: Some(input.to_string()),
+///             ..Morpheme::new(input, PartOfSpeech::Other)
+///         }])
+///     }
+/// }
+///
+/// let romanizer = Romanizer::builder().build_with_tokenizer(Katakana);
+/// assert_eq!(romanizer.romanize("カタカナ"), "katakana");
+/// ```
+pub trait Tokenizer {
+    /// Split `input` into morphemes in the order they appear in it. Text which isn't covered by
+    /// any morpheme, e.g. whitespace, is kept as is.
+    fn tokenize<'a>(&self, input: &'a str) -> Result<Vec<Morpheme<'a>>, RomanizeError>;
+}
+
+/// A single word found by a [`Tokenizer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Morpheme<'a> {
+    /// The word as it appears in the input. Should be a slice of the input so its position can be
+    /// determined without searching for it.
+    pub surface: &'a str,
+    /// Part-of-speech including its subdivisions.
+    pub pos: PartOfSpeech,
+    /// Dictionary form of the word, e.g. 食べる for 食べ.
+    pub base_form: Option<String>,
+    /// Reading in katakana as written, e.g. トウキョウ for 東京.
+    pub reading: Option<String>,
+    /// Pronunciation in katakana, e.g. トーキョー for 東京.
+    pub pronunciation: Option<String>,
+}
+
+impl<'a> Morpheme<'a> {
+    /// A morpheme without base form, reading or pronunciation.
+    pub fn new(surface: &'a str, pos: PartOfSpeech) -> Morpheme<'a> {
+        Morpheme {
+            surface,
+            pos,
+            base_form: None,
+            reading: None,
+            pronunciation: None,
+        }
+    }
+
+    /// Parse the comma-separated features of an IPADIC entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use romanize::{Morpheme, NounKind, PartOfSpeech, ProperNounKind};
+    ///
+    /// let morpheme = Morpheme::from_ipadic_features(
+    ///     "東京",
+    ///     "名詞,固有名詞,地域,一般,*,*,東京,トウキョウ,トーキョー",
+    /// );
+    /// assert_eq!(
+    ///     morpheme.pos,
+    ///     PartOfSpeech::Noun(NounKind::Proper(ProperNounKind::Place)),
+    /// );
+    /// assert_eq!(morpheme.pronunciation, Some("トーキョー".to_string()));
+    /// ```
+    pub fn from_ipadic_features(surface: &'a str, feature: &str) -> Morpheme<'a> {
+        // Features:
+        // 0 Part-of-speech
+        // 1 Part-of-speech subdivision class 1
+        // 2 Partspeech subdivision class 2
+        // 3 Partspeech subdivision class 3
+        // 4 Utilization type
+        // 5 Utilization form
+        // 6 Original form
+        // 7 Reading
+        // 8 Pronunciation
+        let feature = split_features(feature);
+        let field = |i: usize| field(&feature, i);
+
+        Morpheme {
+            surface,
+            pos: PartOfSpeech::from_features(&feature[..feature.len().min(4)]),
+            base_form: field(6),
+            reading: field(7),
+            pronunciation: field(8),
+        }
+    }
+
+    /// Parse the comma-separated features of a UniDic entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use romanize::{Morpheme, NounKind, PartOfSpeech};
+    ///
+    /// let morpheme = Morpheme::from_unidic_features(
+    ///     "綺麗",
+    ///     "形状詞,一般,*,*,*,*,キレイ,綺麗,綺麗,キレー,綺麗,キレー,漢,*,*,*,*,*,*,\
+    ///      相,キレイ,キレイ,キレイ,キレイ,1,C2,*,10245,37203",
+    /// );
+    /// assert_eq!(morpheme.pos, PartOfSpeech::Noun(NounKind::AdjectivalNoun));
+    /// assert_eq!(morpheme.reading, Some("キレイ".to_string()));
+    /// assert_eq!(morpheme.pronunciation, Some("キレー".to_string()));
+    /// ```
+    pub fn from_unidic_features(surface: &'a str, feature: &str) -> Morpheme<'a> {
+        // Features (unidic-cwj 2.2 and later):
+        // 0-3 Part-of-speech and its subdivisions
+        // 4 Conjugation type
+        // 5 Conjugation form
+        // 6 Reading of the lemma
+        // 7 Lemma
+        // 8 Orthography
+        // 9 Pronunciation
+        // 10 Orthography of the base form
+        // 11 Pronunciation of the base form
+        // ...
+        // 20 Reading (kana)
+        let feature = split_features(feature);
+        let field = |i: usize| field(&feature, i);
+
+        let mut pos = PartOfSpeech::from_unidic_features(&feature[..feature.len().min(4)]);
+        // Unlike IPADIC, UniDic doesn't tell honorifics apart from other suffixes
+        let honorific = field(7)
+            .map(|lemma| HONORIFICS.contains(&&lemma[..]))
+            .unwrap_or(false);
+        if honorific && pos == PartOfSpeech::Noun(NounKind::Suffix(SuffixKind::General)) {
+            pos = PartOfSpeech::Noun(NounKind::Suffix(SuffixKind::Person));
+        }
+
+        Morpheme {
+            surface,
+            pos,
+            base_form: field(10),
+            // Older releases (unidic-mecab) have no kana field, fall back to the pronunciation
+            reading: field(20).or_else(|| field(9)),
+            pronunciation: field(9),
+        }
+    }
+
+    /// Parse the comma-separated features of an entry of a dictionary in `format`.
+    pub fn from_features(
+        surface: &'a str,
+        feature: &str,
+        format: DictionaryFormat,
+    ) -> Morpheme<'a> {
+        match format {
+            DictionaryFormat::Ipadic => Morpheme::from_ipadic_features(surface, feature),
+            DictionaryFormat::Unidic => Morpheme::from_unidic_features(surface, feature),
+        }
+    }
+}
+
+/// Lemmas of the honorific suffixes (名詞,接尾,人名 in IPADIC) in UniDic.
+const HONORIFICS: &[&str] = &["さん", "様", "君", "ちゃん", "殿", "氏", "嬢", "たん"];
+
+/// The layout of the features of a dictionary, which determines where the part-of-speech and the
+/// readings are found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DictionaryFormat {
+    /// IPADIC (the bundled dictionary) and dictionaries derived from it like NEologd.
+    #[default]
+    Ipadic,
+    /// UniDic, e.g. unidic-cwj.
+    Unidic,
+}
+
+/// The default [`Tokenizer`] using igo dictionaries.
+pub struct IgoTokenizer {
+    tagger: tagger::Tagger,
+    format: DictionaryFormat,
+}
+
+impl IgoTokenizer {
+    /// Load the IPADIC-style igo dictionary in `dictionary_dir`.
+    pub fn new<P: AsRef<Path>>(dictionary_dir: P) -> Result<IgoTokenizer, RomanizeError> {
+        IgoTokenizer::with_format(dictionary_dir, DictionaryFormat::Ipadic)
+    }
+
+    /// Load the igo dictionary in `dictionary_dir` whose features are laid out as in `format`.
+    pub fn with_format<P: AsRef<Path>>(
+        dictionary_dir: P,
+        format: DictionaryFormat,
+    ) -> Result<IgoTokenizer, RomanizeError> {
+        let dictionary_dir = dictionary_dir.as_ref();
+        let mut files = HashMap::new();
+        for &name in dictionary::DICTIONARY_FILES {
+            let path = dictionary_dir.join(name);
+            match fs::read(&path) {
+                Ok(bytes) => files.insert(name.to_string(), bytes),
+                Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+                    return Err(RomanizeError::MissingDictionaryFile(path))
+                }
+                Err(e) => return Err(e.into()),
+            };
+        }
+        // Name the files of the directory in errors
+        IgoTokenizer::from_files(&files, format).map_err(|e| match e {
+            RomanizeError::MalformedDictionaryFile(name, reason) => {
+                RomanizeError::MalformedDictionaryFile(dictionary_dir.join(name), reason)
+            }
+            e => e,
+        })
+    }
+
+    /// Load the dictionary of `archive` directly from memory without writing anything to the file
+    /// system.
+    pub fn from_archive(
+        archive: &DictionaryArchive,
+        format: DictionaryFormat,
+    ) -> Result<IgoTokenizer, RomanizeError> {
+        let files = archive.read_files(&mut LoadStats::default())?;
+        IgoTokenizer::from_files(&files, format)
+    }
+
+    /// Load an extracted dictionary or one in memory.
+    pub(crate) fn load(
+        source: &DictionarySource,
+        format: DictionaryFormat,
+    ) -> Result<IgoTokenizer, RomanizeError> {
+        match *source {
+            DictionarySource::Dir(ref path) => IgoTokenizer::with_format(path, format),
+            DictionarySource::Files(ref files) => IgoTokenizer::from_files(files, format),
+        }
+    }
+
+    fn from_files(
+        files: &HashMap<String, Vec<u8>>,
+        format: DictionaryFormat,
+    ) -> Result<IgoTokenizer, RomanizeError> {
+        Ok(IgoTokenizer {
+            tagger: tagger::Tagger::from_files(files)?,
+            format,
+        })
+    }
+
+    /// Add the entries of `user_dictionary` to the dictionary so they compete with its words, see
+    /// [`UserDictionary`].
+    ///
+    /// Fails with [`RomanizeError::InvalidUserDictionary`] if the context ids of an entry are out
+    /// of range for the dictionary.
+    pub fn add_user_dictionary(
+        &mut self,
+        user_dictionary: &UserDictionary,
+    ) -> Result<(), RomanizeError> {
+        self.tagger.add_words(user_dictionary.words())
+    }
+
+    /// The layout of the features of the dictionary.
+    pub fn format(&self) -> DictionaryFormat {
+        self.format
+    }
+}
+
+impl Tokenizer for IgoTokenizer {
+    fn tokenize<'a>(&self, input: &'a str) -> Result<Vec<Morpheme<'a>>, RomanizeError> {
+        Ok(self
+            .tagger
+            .parse(input)?
+            .into_iter()
+            .map(|part| {
+                // Entries of the user dictionary are always in the layout of IPADIC
+                let format = if part.user {
+                    DictionaryFormat::Ipadic
+                } else {
+                    self.format
+                };
+                Morpheme::from_features(part.surface, &part.feature, format)
+            })
+            .collect())
+    }
+}
+
+/// Split features at commas. Fields containing commas are quoted (`"1,000"`) in UniDic.
+pub(crate) fn split_features(feature: &str) -> Vec<&str> {
+    let mut fields = Vec::new();
+    let mut rest = feature;
+    loop {
+        if rest.starts_with('"') {
+            if let Some(end) = rest[1..].find('"') {
+                fields.push(&rest[1..=end]);
+                rest = &rest[end + 2..];
+                match rest.find(',') {
+                    Some(i) => rest = &rest[i + 1..],
+                    None => return fields,
+                }
+                continue;
+            }
+        }
+        match rest.find(',') {
+            Some(i) => {
+                fields.push(&rest[..i]);
+                rest = &rest[i + 1..];
+            }
+            None => {
+                fields.push(rest);
+                return fields;
+            }
+        }
+    }
+}
+
+/// The `i`th feature unless it's empty or `*`.
+fn field(feature: &[&str], i: usize) -> Option<String> {
+    feature
+        .get(i)
+        .filter(|f| **f != "*" && !f.is_empty())
+        .map(|f| f.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pos::ParticleKind;
+
+    #[test]
+    fn split() {
+        assert_eq!(split_features("名詞,数詞,*"), vec!["名詞", "数詞", "*"]);
+        assert_eq!(
+            split_features("補助記号,読点,*,*,*,*,\",\",\",\""),
+            vec!["補助記号", "読点", "*", "*", "*", "*", ",", ","]
+        );
+        assert_eq!(split_features("\"1,000\""), vec!["1,000"]);
+        assert_eq!(split_features(""), vec![""]);
+    }
+
+    #[test]
+    fn unidic_without_kana() {
+        // unidic-mecab 2.1.2
+        let morpheme = Morpheme::from_unidic_features(
+            "は",
+            "助詞,係助詞,*,*,*,*,ハ,は,は,ワ,は,ワ,和,*,*,*,*",
+        );
+        assert_eq!(morpheme.pos, PartOfSpeech::Particle(ParticleKind::Binding));
+        assert_eq!(morpheme.base_form, Some("は".to_string()));
+        assert_eq!(morpheme.reading, Some("ワ".to_string()));
+        assert_eq!(morpheme.pronunciation, Some("ワ".to_string()));
+    }
+
+    #[test]
+    fn unidic_honorifics() {
+        let morpheme = Morpheme::from_unidic_features(
+            "さん",
+            "接尾辞,名詞的,一般,*,*,*,サン,さん,さん,サン,さん,サン,和,*,*,*,*,*,*,\
+             接尾体,サン,サン,サン,サン,*,C3,*,14434,52456",
+        );
+        assert_eq!(
+            morpheme.pos,
+            PartOfSpeech::Noun(NounKind::Suffix(SuffixKind::Person))
+        );
+
+        let morpheme = Morpheme::from_unidic_features(
+            "さま",
+            "接尾辞,名詞的,一般,*,*,*,サマ,様,さま,サマ,さま,サマ,和,*,*,*,*,*,*,\
+             接尾体,サマ,サマ,サマ,サマ,*,C3,*,14688,53458",
+        );
+        assert_eq!(
+            morpheme.pos,
+            PartOfSpeech::Noun(NounKind::Suffix(SuffixKind::Person))
+        );
+
+        // Other suffixes are left alone
+        let morpheme = Morpheme::from_unidic_features(
+            "的",
+            "接尾辞,形状詞的,*,*,*,*,テキ,的,的,テキ,的,テキ,漢,*,*,*,*,*,*,\
+             接尾体,テキ,テキ,テキ,テキ,*,C3,*,7001,25465",
+        );
+        assert_eq!(
+            morpheme.pos,
+            PartOfSpeech::Noun(NounKind::Suffix(SuffixKind::AdjectivalNoun))
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use std::fmt;
+
+use kana::{self, RomanizationSystem};
+use options::RomanizeOptions;
+use token::Token;
+
+/// Converts the katakana reading of a token into Latin script.
+///
+/// By default the [`RomanizationSystem`] of the [`RomanizeOptions`] is used; set another
+/// transliterator with [`RomanizerBuilder::transliterator`](::RomanizerBuilder::transliterator)
+/// to follow a house style.
+///
+/// # Examples
+///
+#[cfg_attr(
+    not(any(feature = "bundled-ipadic", feature = "bundled-ipadic-xz")),
+    doc = "```ignore"
+)]
+#[cfg_attr(
+    any(feature = "bundled-ipadic", feature = "bundled-ipadic-xz"),
+    doc = "```"
+)]
+/// use romanize::{RomanizationSystem, RomanizeOptions, Romanizer, Token, Transliterator};
+///
+/// /// Modified Hepburn but with "oh" for long o like in some passports.
+/// struct HouseStyle;
+///
+/// impl Transliterator for HouseStyle {
+///     fn transliterate(&self, kana: &str, token: &Token, options: &RomanizeOptions) -> String {
+///         RomanizationSystem::ModifiedHepburn
+///             .transliterate(kana, token, options)
+///             .replace('ō', "oh")
+///     }
+/// }
+///
+/// let romanizer = Romanizer::builder()
+///     .transliterator(HouseStyle)
+///     .build()
+///     .unwrap();
+/// assert_eq!(romanizer.romanize("東京"), "Tohkyoh");
+/// ```
+pub trait Transliterator: Send + Sync {
+    /// Romanize `kana`, the reading or pronunciation of `token` in katakana (or its surface if the
+    /// token itself is written in katakana). `options` are the options of the current call.
+    fn transliterate(&self, kana: &str, token: &Token, options: &RomanizeOptions) -> String;
+}
+
+/// Uses this system regardless of [`RomanizeOptions::system`]. Long vowels and syllabic n follow
+/// [`RomanizeOptions::long_vowels`] and [`RomanizeOptions::syllabic_n`] or the convention of this
+/// system (and the separator) if they're unset.
+impl Transliterator for RomanizationSystem {
+    fn transliterate(&self, kana: &str, _token: &Token, options: &RomanizeOptions) -> String {
+        let long_vowels = options
+            .long_vowels
+            .unwrap_or_else(|| self.long_vowel_style());
+        let syllabic_n = options.syllabic_n_style_for(*self);
+        kana::transliterate(kana, *self, long_vowels, syllabic_n, options.sokuon)
+    }
+}
+
+impl fmt::Debug for dyn Transliterator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Transliterator")
+    }
+}
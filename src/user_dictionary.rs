@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use error::RomanizeError;
+use tagger::UserWord;
+use tokenizer::split_features;
+
+/// Words to recognize in addition to the system dictionary, e.g. names of artists or brands.
+///
+/// Like in MeCab, the entries are added to the lattice of the tagger and compete with the words of
+/// the dictionary for the cheapest segmentation. Entries without a cost get a low one so they win
+/// over several words covering the same text, e.g. きゃりーぱみゅぱみゅ is kept together instead of
+/// being split into unknown words. They don't split longer words of the dictionary though: an
+/// entry for ミク leaves ミクロ alone. Words of the dictionary with the same surface as an entry are
+/// replaced by it, e.g. to change the reading of 東京.
+///
+/// Each line of a user dictionary has the form `surface,part-of-speech,reading[,pronunciation]`.
+/// The part-of-speech uses the IPADIC names with the subdivisions joined by `-`, e.g.
+/// `名詞-固有名詞-人名`. The reading and pronunciation are in katakana; a missing pronunciation
+/// defaults to the reading while `*` as reading keeps the surface as is. Empty lines and lines
+/// starting with `#` are ignored.
+///
+/// Lines of MeCab user dictionaries for IPADIC are accepted as well:
+/// `surface,left-id,right-id,cost,pos,pos1,pos2,pos3,conjugation type,conjugation form,base form,
+/// reading[,pronunciation]`. Their context ids and cost are used as given; empty ones are filled in
+/// like for the simple format. Missing context ids are taken from a word of the dictionary with
+/// the same part-of-speech.
+///
+/// # Examples
+///
+#[cfg_attr(
+    not(any(feature = "bundled-ipadic", feature = "bundled-ipadic-xz")),
+    doc = "```ignore"
+)]
+#[cfg_attr(
+    any(feature = "bundled-ipadic", feature = "bundled-ipadic-xz"),
+    doc = "```"
+)]
+/// use romanize::{Romanizer, UserDictionary};
+///
+/// let user_dictionary = UserDictionary::parse(
+///     "# Kept as is instead of being split into U, & and I
+///      U&I,名詞-固有名詞-組織,*
+///      あの丘,名詞-固有名詞-地域,アノオカ",
+/// )
+/// .unwrap();
+/// let romanizer = Romanizer::builder()
+///     .user_dictionary(user_dictionary)
+///     .build()
+///     .unwrap();
+/// assert_eq!(
+///     romanizer.romanize("U&I ～夕日の綺麗なあの丘で～"),
+///     "U&I ~Yūhi no Kirei na Anooka de~",
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserDictionary {
+    entries: HashMap<String, Entry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    /// Features in the layout of IPADIC.
+    feature: String,
+    /// Context ids and cost of MeCab entries.
+    left_id: Option<i16>,
+    right_id: Option<i16>,
+    cost: Option<i16>,
+    /// Line defining the entry or 0 if it was added with [`UserDictionary::add`].
+    line: usize,
+}
+
+impl UserDictionary {
+    /// An empty user dictionary.
+    pub fn new() -> UserDictionary {
+        UserDictionary::default()
+    }
+
+    /// Parse a user dictionary in the format described above. Later entries replace earlier ones
+    /// with the same surface.
+    ///
+    /// Fails with [`RomanizeError::InvalidUserDictionary`] if a line is malformed.
+    pub fn parse(csv: &str) -> Result<UserDictionary, RomanizeError> {
+        let mut dictionary = UserDictionary::new();
+        for (i, line) in csv.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields = split_features(line);
+            let line = i + 1;
+            let invalid = |reason: &str| RomanizeError::InvalidUserDictionary(line, reason.into());
+            let simple = |i: usize| fields[i].trim();
+            match fields.len() {
+                3 => dictionary.insert(simple(0), simple(1), simple(2), simple(2), line),
+                4 => dictionary.insert(simple(0), simple(1), simple(2), simple(3), line),
+                len if len >= 12 => dictionary.insert_mecab(&fields, line),
+                _ => {
+                    return Err(invalid(
+                        "expected 3 or 4 fields or at least 12 for a MeCab entry",
+                    ))
+                }
+            }
+            .map_err(invalid)?;
+        }
+        Ok(dictionary)
+    }
+
+    /// Load a user dictionary from a UTF-8 encoded file, see [`UserDictionary::parse`].
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<UserDictionary, RomanizeError> {
+        UserDictionary::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Add a single word. `pos` and `reading` have the same format as in a file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut user_dictionary = romanize::UserDictionary::new();
+    /// user_dictionary.add("初音ミク", "名詞-固有名詞-人名", "ハツネミク").unwrap();
+    /// ```
+    pub fn add(&mut self, surface: &str, pos: &str, reading: &str) -> Result<(), RomanizeError> {
+        self.insert(surface, pos, reading, reading, 0)
+            .map_err(|reason| RomanizeError::InvalidUserDictionary(0, reason.into()))
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn insert(
+        &mut self,
+        surface: &str,
+        pos: &str,
+        reading: &str,
+        pronunciation: &str,
+        line: usize,
+    ) -> Result<(), &'static str> {
+        if surface.is_empty() {
+            return Err("empty surface");
+        }
+        let mut pos = pos.split('-').filter(|p| !p.is_empty()).collect::<Vec<_>>();
+        if pos.is_empty() || pos.len() > 4 {
+            return Err("expected a part-of-speech with up to three subdivisions");
+        }
+        pos.resize(4, "*");
+        let kana = |kana: &str| if kana.is_empty() { "*" } else { kana }.to_string();
+
+        // Same layout as the features of IPADIC
+        let feature = format!(
+            "{},*,*,{},{},{}",
+            pos.join(","),
+            surface,
+            kana(reading),
+            kana(pronunciation)
+        );
+        self.entries.insert(
+            surface.to_string(),
+            Entry {
+                feature,
+                left_id: None,
+                right_id: None,
+                cost: None,
+                line,
+            },
+        );
+        Ok(())
+    }
+
+    /// Add an entry in the layout of a MeCab user dictionary for IPADIC.
+    fn insert_mecab(&mut self, fields: &[&str], line: usize) -> Result<(), &'static str> {
+        if fields[0].is_empty() {
+            return Err("empty surface");
+        }
+        // Empty ids or costs are filled in by MeCab's dictionary compiler
+        let mut numbers = [None; 3];
+        for (number, field) in numbers.iter_mut().zip(&fields[1..4]) {
+            if !field.is_empty() {
+                *number = Some(
+                    field
+                        .parse::<i16>()
+                        .map_err(|_| "context ids and cost must be 16-bit integers")?,
+                );
+            }
+        }
+
+        let mut features = fields[4..].to_vec();
+        if features.len() == 8 {
+            // The pronunciation defaults to the reading
+            features.push(features[7]);
+        }
+        let feature = features
+            .iter()
+            .map(|field| {
+                if field.contains(',') {
+                    format!("\"{}\"", field)
+                } else {
+                    field.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        self.entries.insert(
+            fields[0].to_string(),
+            Entry {
+                feature,
+                left_id: numbers[0],
+                right_id: numbers[1],
+                cost: numbers[2],
+                line,
+            },
+        );
+        Ok(())
+    }
+
+    /// The entries to add to the tagger.
+    pub(crate) fn words<'a>(&'a self) -> impl Iterator<Item = UserWord<'a>> {
+        self.entries.iter().map(|(surface, entry)| UserWord {
+            surface,
+            feature: &entry.feature,
+            left_id: entry.left_id,
+            right_id: entry.right_id,
+            cost: entry.cost,
+            line: entry.line,
+        })
+    }
+}
+
+/// The longest key of `entries` which is a prefix of `input`. `max_len` is the length of the
+/// longest key in bytes.
+pub(crate) fn longest_match<'a, V>(
+    entries: &'a HashMap<String, V>,
+    max_len: usize,
+    input: &str,
+) -> Option<(&'a String, &'a V)> {
+    (1..=max_len.min(input.len()))
+        .rev()
+        .filter(|&len| input.is_char_boundary(len))
+        .find_map(|len| entries.get_key_value(&input[..len]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let dictionary = UserDictionary::parse(
+            "# Comment\n\n東京,名詞-固有名詞-地域-一般,トウキョウ,トーキョー\n東,名詞,アズマ\n",
+        )
+        .unwrap();
+        assert_eq!(dictionary.len(), 2);
+        assert_eq!(
+            dictionary.entries["東京"],
+            Entry {
+                feature: "名詞,固有名詞,地域,一般,*,*,東京,トウキョウ,トーキョー".to_string(),
+                left_id: None,
+                right_id: None,
+                cost: None,
+                line: 3,
+            }
+        );
+        assert_eq!(
+            dictionary.entries["東"].feature,
+            "名詞,*,*,*,*,*,東,アズマ,アズマ"
+        );
+
+        let dictionary = UserDictionary::parse(
+            "東,1285,1285,5000,名詞,固有名詞,人名,姓,*,*,東,アズマ,アズマ\n\
+             東京,,,,名詞,固有名詞,地域,一般,*,*,東京,トウキョウ\n",
+        )
+        .unwrap();
+        assert_eq!(
+            dictionary.entries["東"],
+            Entry {
+                feature: "名詞,固有名詞,人名,姓,*,*,東,アズマ,アズマ".to_string(),
+                left_id: Some(1285),
+                right_id: Some(1285),
+                cost: Some(5000),
+                line: 1,
+            }
+        );
+        assert_eq!(
+            dictionary.entries["東京"],
+            Entry {
+                feature: "名詞,固有名詞,地域,一般,*,*,東京,トウキョウ,トウキョウ".to_string(),
+                left_id: None,
+                right_id: None,
+                cost: None,
+                line: 2,
+            }
+        );
+
+        match UserDictionary::parse("東京,名詞\n") {
+            Err(RomanizeError::InvalidUserDictionary(1, _)) => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+        match UserDictionary::parse("東京,名詞,トウキョウ\n,名詞,ア") {
+            Err(RomanizeError::InvalidUserDictionary(2, _)) => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+        match UserDictionary::parse("東,a,1,1,名詞,一般,*,*,*,*,東,ヒガシ,ヒガシ") {
+            Err(RomanizeError::InvalidUserDictionary(1, _)) => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+        match UserDictionary::parse("東,1,1,40000,名詞,一般,*,*,*,*,東,ヒガシ,ヒガシ") {
+            Err(RomanizeError::InvalidUserDictionary(1, _)) => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+}